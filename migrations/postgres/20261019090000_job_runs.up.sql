@@ -0,0 +1,6 @@
+create table job_runs (
+  name text primary key not null,
+  last_run_at timestamptz not null,
+  last_success_at timestamptz,
+  last_error text
+);
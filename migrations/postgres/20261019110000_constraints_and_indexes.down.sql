@@ -0,0 +1,9 @@
+drop index todos_done_by_idx;
+drop index todos_assigned_user_idx;
+drop index todos_chat_id_idx;
+
+alter table todos
+  drop constraint todos_done_by_fkey,
+  drop constraint todos_assigned_user_fkey;
+
+alter table chat_members drop constraint chat_members_chat_id_telegram_user_id_key;
@@ -0,0 +1,48 @@
+-- merge duplicate chat members into one of them
+create temporary table duplicate_chat_members as
+select
+  c.id,
+  k.id as keeper_id
+from
+  chat_members as c
+  join (
+    select chat_id, telegram_user_id, min(id::text)::uuid as id
+    from chat_members
+    group by chat_id, telegram_user_id
+  ) as k on k.chat_id = c.chat_id and k.telegram_user_id = c.telegram_user_id
+where
+  c.id <> k.id;
+
+update todos set assigned_user = d.keeper_id from duplicate_chat_members as d where todos.assigned_user = d.id;
+update todos set done_by = d.keeper_id from duplicate_chat_members as d where todos.done_by = d.id;
+delete from chat_members where id in (select id from duplicate_chat_members);
+
+drop table duplicate_chat_members;
+
+-- hand todos of members that do not exist to a member of their chat, only chats without members
+-- lose their todos
+update todos
+set
+  assigned_user = coalesce(
+    (select m.id from chat_members as m where m.chat_id = todos.chat_id order by random() limit 1),
+    assigned_user
+  )
+where
+  assigned_user not in (select id from chat_members);
+
+delete from todos where assigned_user not in (select id from chat_members);
+
+-- done todos stay done
+update todos set done_by = assigned_user where done_by not in (select id from chat_members);
+
+alter table chat_members
+  add constraint chat_members_chat_id_telegram_user_id_key unique (chat_id, telegram_user_id);
+
+-- removing members must not delete or reopen todos
+alter table todos
+  add constraint todos_assigned_user_fkey foreign key (assigned_user) references chat_members (id) on delete restrict,
+  add constraint todos_done_by_fkey foreign key (done_by) references chat_members (id) on delete restrict;
+
+create index todos_chat_id_idx on todos (chat_id);
+create index todos_assigned_user_idx on todos (assigned_user);
+create index todos_done_by_idx on todos (done_by);
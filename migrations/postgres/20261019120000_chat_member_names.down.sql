@@ -0,0 +1,6 @@
+alter table chat_members
+  drop column last_name,
+  drop column first_name,
+  drop column username;
+
+alter table chat_members rename column display_name to username;
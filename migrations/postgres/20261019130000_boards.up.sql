@@ -0,0 +1,5 @@
+create table boards (
+  chat_id int8 primary key not null,
+  message_id int4 not null,
+  refreshed_on date
+);
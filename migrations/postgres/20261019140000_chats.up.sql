@@ -0,0 +1,4 @@
+create table chats (
+  id int8 primary key not null,
+  title text not null
+);
@@ -0,0 +1,3 @@
+create table notification_subscribers (
+  telegram_user_id int8 primary key not null
+);
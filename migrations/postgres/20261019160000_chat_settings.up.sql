@@ -0,0 +1,4 @@
+create table chat_settings (
+  chat_id int8 primary key not null,
+  language text not null default 'en'
+);
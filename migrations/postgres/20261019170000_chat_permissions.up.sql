@@ -0,0 +1,6 @@
+create table chat_permissions (
+  chat_id int8 not null,
+  operation text not null,
+  permission text not null,
+  primary key (chat_id, operation)
+);
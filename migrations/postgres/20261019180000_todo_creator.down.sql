@@ -0,0 +1,7 @@
+alter table chat_settings drop column assign_creator;
+
+drop index todos_created_by_idx;
+
+alter table todos
+  drop column created_at,
+  drop column created_by;
@@ -0,0 +1,8 @@
+-- the creator of todos added before is unknown
+alter table todos
+  add column created_by uuid references chat_members (id) on delete set null,
+  add column created_at timestamptz;
+
+create index todos_created_by_idx on todos (created_by);
+
+alter table chat_settings add column assign_creator boolean not null default true;
@@ -0,0 +1 @@
+drop table undo_journal;
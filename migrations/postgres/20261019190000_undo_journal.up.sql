@@ -0,0 +1,10 @@
+-- states of todos before an action changed them, restored by /undo
+create table undo_journal (
+  id uuid primary key not null,
+  chat_id int8 not null,
+  "action" text not null,
+  todo jsonb not null,
+  recorded_at timestamptz not null
+);
+
+create index undo_journal_chat_id_recorded_at_idx on undo_journal (chat_id, recorded_at);
@@ -0,0 +1,5 @@
+drop table job_runs;
+
+drop table todos;
+
+drop table chat_members;
@@ -0,0 +1,25 @@
+create table chat_members (
+  id blob primary key not null,
+  telegram_user_id integer not null,
+  chat_id integer not null,
+  username text not null,
+  todo_weight integer not null default 100
+);
+
+create table todos (
+  id blob primary key not null,
+  chat_id integer not null,
+  "description" text not null,
+  --
+  interval_days integer,
+  assigned_user blob not null,
+  scheduled_for text not null default current_date,
+  done_by blob
+);
+
+create table job_runs (
+  name text primary key not null,
+  last_run_at text not null,
+  last_success_at text,
+  last_error text
+);
@@ -0,0 +1,27 @@
+create table chat_members_old (
+  id blob primary key not null,
+  telegram_user_id integer not null,
+  chat_id integer not null,
+  username text not null,
+  todo_weight integer not null default 100
+);
+
+create table todos_old (
+  id blob primary key not null,
+  chat_id integer not null,
+  "description" text not null,
+  --
+  interval_days integer,
+  assigned_user blob not null,
+  scheduled_for text not null default current_date,
+  done_by blob
+);
+
+insert into chat_members_old select id, telegram_user_id, chat_id, username, todo_weight from chat_members;
+insert into todos_old select id, chat_id, "description", interval_days, assigned_user, scheduled_for, done_by from todos;
+
+drop table todos;
+drop table chat_members;
+
+alter table chat_members_old rename to chat_members;
+alter table todos_old rename to todos;
@@ -0,0 +1,74 @@
+-- sqlite can't add constraints to existing tables, so both tables are rebuilt
+
+-- keep one of each duplicate chat member
+create table chat_members_new (
+  id blob primary key not null,
+  telegram_user_id integer not null,
+  chat_id integer not null,
+  username text not null,
+  todo_weight integer not null default 100,
+  unique (chat_id, telegram_user_id)
+);
+
+insert into chat_members_new (id, telegram_user_id, chat_id, username, todo_weight)
+select id, telegram_user_id, chat_id, username, todo_weight
+from chat_members
+where rowid in (select min(rowid) from chat_members group by chat_id, telegram_user_id);
+
+-- removing members must not delete or reopen todos
+create table todos_new (
+  id blob primary key not null,
+  chat_id integer not null,
+  "description" text not null,
+  --
+  interval_days integer,
+  assigned_user blob not null references chat_members_new (id) on delete restrict,
+  scheduled_for text not null default current_date,
+  done_by blob references chat_members_new (id) on delete restrict
+);
+
+-- point todos of duplicate members to the kept one and hand todos of members that do not exist to a
+-- member of their chat, only chats without members lose their todos
+create temporary table todo_members as
+select
+  t.id,
+  coalesce(
+    a.id,
+    (select m.id from chat_members_new as m where m.chat_id = t.chat_id order by random() limit 1)
+  ) as assigned_user,
+  d.id as done_by
+from
+  todos as t
+  left join chat_members as old_a on old_a.id = t.assigned_user
+  left join chat_members_new as a on a.chat_id = old_a.chat_id and a.telegram_user_id = old_a.telegram_user_id
+  left join chat_members as old_d on old_d.id = t.done_by
+  left join chat_members_new as d on d.chat_id = old_d.chat_id and d.telegram_user_id = old_d.telegram_user_id;
+
+-- done todos stay done
+insert into todos_new (id, chat_id, "description", interval_days, assigned_user, scheduled_for, done_by)
+select
+  t.id,
+  t.chat_id,
+  t."description",
+  t.interval_days,
+  m.assigned_user,
+  t.scheduled_for,
+  case when t.done_by is null then null else coalesce(m.done_by, m.assigned_user) end
+from
+  todos as t
+  join todo_members as m on m.id = t.id
+where
+  m.assigned_user is not null;
+
+drop table todo_members;
+
+drop table todos;
+drop table chat_members;
+
+-- renaming also updates the references of todos_new
+alter table chat_members_new rename to chat_members;
+alter table todos_new rename to todos;
+
+create index todos_chat_id_idx on todos (chat_id);
+create index todos_assigned_user_idx on todos (assigned_user);
+create index todos_done_by_idx on todos (done_by);
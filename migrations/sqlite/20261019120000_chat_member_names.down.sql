@@ -0,0 +1,5 @@
+alter table chat_members drop column last_name;
+alter table chat_members drop column first_name;
+alter table chat_members drop column username;
+
+alter table chat_members rename column display_name to username;
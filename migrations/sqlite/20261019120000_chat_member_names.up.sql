@@ -0,0 +1,8 @@
+alter table chat_members rename column username to display_name;
+
+alter table chat_members add column username text;
+alter table chat_members add column first_name text not null default '';
+alter table chat_members add column last_name text;
+
+-- the names are refreshed with the next message of each member
+update chat_members set first_name = display_name;
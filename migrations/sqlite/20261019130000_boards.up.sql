@@ -0,0 +1,5 @@
+create table boards (
+  chat_id integer primary key not null,
+  message_id integer not null,
+  refreshed_on text
+);
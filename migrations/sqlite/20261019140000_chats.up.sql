@@ -0,0 +1,4 @@
+create table chats (
+  id integer primary key not null,
+  title text not null
+);
@@ -0,0 +1,4 @@
+create table chat_settings (
+  chat_id integer primary key not null,
+  language text not null default 'en'
+);
@@ -0,0 +1,23 @@
+alter table chat_settings drop column assign_creator;
+
+-- sqlite can't drop columns with references
+create table todos_old (
+  id blob primary key not null,
+  chat_id integer not null,
+  "description" text not null,
+  --
+  interval_days integer,
+  assigned_user blob not null references chat_members (id) on delete restrict,
+  scheduled_for text not null default current_date,
+  done_by blob references chat_members (id) on delete restrict
+);
+
+insert into todos_old (id, chat_id, "description", interval_days, assigned_user, scheduled_for, done_by)
+select id, chat_id, "description", interval_days, assigned_user, scheduled_for, done_by from todos;
+
+drop table todos;
+alter table todos_old rename to todos;
+
+create index todos_chat_id_idx on todos (chat_id);
+create index todos_assigned_user_idx on todos (assigned_user);
+create index todos_done_by_idx on todos (done_by);
@@ -0,0 +1,7 @@
+-- the creator of todos added before is unknown
+alter table todos add column created_by blob references chat_members (id) on delete set null;
+alter table todos add column created_at text;
+
+create index todos_created_by_idx on todos (created_by);
+
+alter table chat_settings add column assign_creator boolean not null default true;
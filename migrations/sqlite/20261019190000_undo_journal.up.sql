@@ -0,0 +1,10 @@
+-- states of todos before an action changed them, restored by /undo
+create table undo_journal (
+  id blob primary key not null,
+  chat_id integer not null,
+  "action" text not null,
+  todo text not null,
+  recorded_at text not null
+);
+
+create index undo_journal_chat_id_recorded_at_idx on undo_journal (chat_id, recorded_at);
@@ -0,0 +1,2 @@
+-- deleting recurring todos is always confirmed, this extends it to all todos
+alter table chat_settings add column confirm_deletes boolean not null default false;
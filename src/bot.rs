@@ -0,0 +1,107 @@
+use crate::{
+    action::Action,
+    context::Context,
+    error::LeditError,
+    handler::{
+        chat_member::{register_chat, register_chat_member},
+        delete_confirmation::{handle_delete_callback, DELETE_CALLBACK_PREFIX},
+        my_todos::handle_check_callback,
+    },
+};
+use frankenstein::{CallbackQuery, GetUpdatesParamsBuilder, Message};
+use tokio::sync::watch;
+
+/// Polls telegram for updates and handles their messages until shutdown.
+pub async fn listen_for_updates(
+    ctx: Context<'_>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), LeditError> {
+    let mut update_params_builder = GetUpdatesParamsBuilder::default();
+    update_params_builder
+        .allowed_updates(vec!["message".to_string(), "callback_query".to_string()]);
+
+    let mut update_params = update_params_builder.build().unwrap();
+
+    while !*shutdown.borrow() {
+        let result = tokio::select! {
+            result = ctx.messenger.get_updates(&update_params) => result,
+            _ = shutdown.changed() => break,
+        };
+
+        tracing::debug!("received telegram api update");
+
+        match result {
+            Ok(updates) => {
+                for update in updates {
+                    if *shutdown.borrow() {
+                        break;
+                    }
+
+                    if let Some(message) = update.message {
+                        handle_message(&message, ctx).await;
+                    }
+                    if let Some(query) = update.callback_query {
+                        handle_callback_query(&query, ctx).await;
+                    }
+
+                    update_params = update_params_builder
+                        .offset(update.update_id + 1)
+                        .build()
+                        .unwrap();
+                }
+            }
+            Err(error) => {
+                tracing::error!("failed to process telegram api update, err: {:?}", error);
+            }
+        }
+    }
+
+    // confirm processed updates so they are not delivered again after a restart
+    if let Some(offset) = update_params.offset {
+        let confirm_params = update_params_builder
+            .offset(offset)
+            .limit(1u32)
+            .build()
+            .unwrap();
+        if let Err(err) = ctx.messenger.get_updates(&confirm_params).await {
+            tracing::error!("failed to confirm processed updates, err: {:?}", err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Registers the sender as chat member and executes the action of the message.
+pub async fn handle_message(message: &Message, ctx: Context<'_>) {
+    let action = Action::from_message(message);
+    tracing::info!("action: {}", action);
+
+    if let Err(err) = register_chat(message, ctx.storage).await {
+        tracing::error!("failed to register chat, err: {}", err);
+    }
+
+    if let Err(err) = register_chat_member(message, ctx.storage).await {
+        tracing::error!("failed to register chat member, err: {}", err);
+    }
+
+    if let Err(err) = action.execute(ctx).await {
+        tracing::error!("failed to respond to action, err: {}", err);
+    }
+}
+
+/// Executes the button press of a callback query.
+pub async fn handle_callback_query(query: &CallbackQuery, ctx: Context<'_>) {
+    tracing::info!("callback query: {:?}", query.data);
+    let now = ctx.clock.now().with_timezone(&ctx.config.default_timezone);
+
+    let data = query.data.as_deref().unwrap_or_default();
+    let result = if data.starts_with(DELETE_CALLBACK_PREFIX) {
+        handle_delete_callback(query, now, ctx.storage, ctx.messenger).await
+    } else {
+        handle_check_callback(query, now, ctx.storage, ctx.messenger).await
+    };
+
+    if let Err(err) = result {
+        tracing::error!("failed to respond to callback query, err: {}", err);
+    }
+}
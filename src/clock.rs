@@ -0,0 +1,64 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use std::sync::Mutex;
+use tokio::{sync::watch, time::sleep};
+
+/// Source of the current time for all date logic, so it can be controlled in tests and simulations.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    fn today(&self, timezone: Tz) -> NaiveDate {
+        self.now().with_timezone(&timezone).date_naive()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that only moves when told to.
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        ManualClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Advances `clock` by `step_minutes` every `tick_ms` until shutdown.
+pub async fn simulate(
+    clock: &ManualClock,
+    step_minutes: i64,
+    tick_ms: u64,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    loop {
+        tokio::select! {
+            _ = sleep(std::time::Duration::from_millis(tick_ms)) => {},
+            _ = shutdown.changed() => break,
+        }
+
+        clock.advance(Duration::minutes(step_minutes));
+        tracing::info!("simulated time: {}", clock.now());
+    }
+}
@@ -0,0 +1,509 @@
+//! Registry of the bot's commands. Parsing, dispatching, the `/help` text and telegram's command
+//! menu are all derived from [`COMMANDS`], so a command is defined in one place.
+
+use crate::{
+    action::Action,
+    context::Context,
+    error::LeditError,
+    handler::{
+        board::handle_board,
+        chat_member::handle_assign_creator,
+        delete_confirmation::handle_confirm_deletes,
+        info::{handle_help, handle_start},
+        language::handle_language,
+        my_todos::handle_my_todos,
+        notification::handle_notify,
+        permission::{check_permission, handle_permissions, Operation},
+        todo::{
+            handle_add_todo, handle_check_todo, handle_delete_todo, handle_list_todos,
+            handle_show_todo,
+        },
+        undo::handle_undo,
+    },
+    i18n::{CommandTexts, Language, Texts},
+    messenger::Messenger,
+};
+use chrono::DateTime;
+use chrono_tz::Tz;
+use frankenstein::{BotCommand, Message, SendMessageParams, SetMyCommandsParamsBuilder};
+use regex::Regex;
+use std::{fmt, future::Future, pin::Pin};
+
+/// A command sent to the bot, with everything its handler needs.
+#[derive(Clone, Copy)]
+pub(crate) struct Call<'a> {
+    /// The text following the name of the command.
+    pub args: &'a str,
+    pub message: &'a Message,
+    pub language: Language,
+    pub texts: &'static Texts,
+    /// The time the command is handled at, in the default timezone.
+    pub now: DateTime<Tz>,
+    pub ctx: Context<'a>,
+}
+
+/// Executes a command and returns the reply, if there is one. Arguments that don't fit the
+/// command are ignored without a reply.
+pub(crate) type Handler = for<'a> fn(Call<'a>) -> HandlerFuture<'a>;
+
+pub(crate) type HandlerFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<SendMessageParams>, LeditError>> + Send + 'a>>;
+
+pub struct Command {
+    /// English name, registered in telegram's command menu.
+    pub(crate) name: &'static str,
+    /// Names in other languages. They work in every chat, the first alias of a language is the one
+    /// shown in its help and command menu.
+    pub(crate) aliases: &'static [(Language, &'static str)],
+    /// Description and usage, commands without are not listed.
+    pub(crate) texts: Option<fn(&Texts) -> &CommandTexts>,
+    pub(crate) handler: Handler,
+    /// Whether the command changes the todos of the chat, so its board is refreshed afterwards.
+    pub(crate) changes_todos: bool,
+}
+
+impl Command {
+    /// English name, registered in telegram's command menu.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Name of the command in the given language.
+    pub fn name_in(&self, language: Language) -> &'static str {
+        self.aliases
+            .iter()
+            .find(|(v, _)| *v == language)
+            .map(|(_, name)| *name)
+            .unwrap_or(self.name)
+    }
+
+    fn is_named(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.name == name || self.aliases.iter().any(|(_, v)| *v == name)
+    }
+}
+
+impl fmt::Debug for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Command").field("name", &self.name).finish()
+    }
+}
+
+pub static COMMANDS: &[Command] = &[
+    Command {
+        name: "start",
+        aliases: &[],
+        texts: None,
+        handler: |call| Box::pin(start(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "help",
+        aliases: &[(Language::German, "hilfe")],
+        texts: Some(|v| &v.help_command),
+        handler: |call| Box::pin(help(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "add",
+        aliases: &[(Language::German, "neu")],
+        texts: Some(|v| &v.add_command),
+        handler: |call| Box::pin(add(call)),
+        changes_todos: true,
+    },
+    Command {
+        name: "todos",
+        aliases: &[(Language::German, "aufgaben")],
+        texts: Some(|v| &v.todos_command),
+        handler: |call| Box::pin(todos(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "board",
+        aliases: &[(Language::German, "tafel")],
+        texts: Some(|v| &v.board_command),
+        handler: |call| Box::pin(board(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "mytodos",
+        aliases: &[(Language::German, "meine")],
+        texts: Some(|v| &v.my_todos_command),
+        handler: |call| Box::pin(my_todos(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "notify",
+        aliases: &[(Language::German, "benachrichtigen")],
+        texts: Some(|v| &v.notify_command),
+        handler: |call| Box::pin(notify(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "show",
+        aliases: &[(Language::German, "zeige")],
+        texts: Some(|v| &v.show_command),
+        handler: |call| Box::pin(show(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "check",
+        aliases: &[(Language::German, "erledigt")],
+        texts: Some(|v| &v.check_command),
+        handler: |call| Box::pin(check(call)),
+        changes_todos: true,
+    },
+    Command {
+        name: "delete",
+        aliases: &[
+            (Language::German, "loeschen"),
+            (Language::German, "löschen"),
+        ],
+        texts: Some(|v| &v.delete_command),
+        handler: |call| Box::pin(delete(call)),
+        changes_todos: true,
+    },
+    Command {
+        name: "undo",
+        aliases: &[(Language::German, "rueckgaengig")],
+        texts: Some(|v| &v.undo_command),
+        handler: |call| Box::pin(undo(call)),
+        changes_todos: true,
+    },
+    Command {
+        name: "language",
+        aliases: &[(Language::German, "sprache")],
+        texts: Some(|v| &v.language_command),
+        handler: |call| Box::pin(language(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "permissions",
+        aliases: &[(Language::German, "rechte")],
+        texts: Some(|v| &v.permissions_command),
+        handler: |call| Box::pin(permissions(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "assigncreator",
+        aliases: &[(Language::German, "erstellerzuteilen")],
+        texts: Some(|v| &v.assign_creator_command),
+        handler: |call| Box::pin(assign_creator(call)),
+        changes_todos: false,
+    },
+    Command {
+        name: "confirmdeletes",
+        aliases: &[(Language::German, "loeschenbestaetigen")],
+        texts: Some(|v| &v.confirm_deletes_command),
+        handler: |call| Box::pin(confirm_deletes(call)),
+        changes_todos: false,
+    },
+];
+
+/// Parses a command like `/add every 2 days: Clean kitchen`, the bot's username may follow the
+/// name as in `/todos@ledit_bot`. Returns `None` if the text is no known command.
+pub fn parse_command<'a>(text: &'a str, message: &'a Message) -> Option<Action<'a>> {
+    let text = text.strip_prefix('/')?;
+    let (word, args) = match text.split_once(char::is_whitespace) {
+        Some((word, args)) => (word, args.trim_start()),
+        None => (text, ""),
+    };
+    let name = word.split('@').next().unwrap_or_default();
+
+    COMMANDS
+        .iter()
+        .find(|v| v.is_named(name))
+        .map(|command| Action::Command {
+            command,
+            args,
+            message,
+        })
+}
+
+/// Registers the listed commands in telegram's command menu, in english by default and with their
+/// aliases for users of the other languages.
+pub async fn register_commands(messenger: &dyn Messenger) -> Result<(), LeditError> {
+    for language in Language::ALL {
+        let mut builder = SetMyCommandsParamsBuilder::default();
+        builder.commands(bot_commands(language));
+        if language != Language::default() {
+            builder.language_code(language.code());
+        }
+
+        messenger.set_my_commands(&builder.build()?).await?;
+    }
+
+    Ok(())
+}
+
+pub fn bot_commands(language: Language) -> Vec<BotCommand> {
+    COMMANDS
+        .iter()
+        .filter_map(|command| {
+            command.texts.map(|texts| BotCommand {
+                command: command.name_in(language).to_string(),
+                description: texts(language.texts()).description.to_string(),
+            })
+        })
+        .collect()
+}
+
+async fn start(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    Ok(Some(handle_start(
+        call.message,
+        call.ctx.config,
+        call.texts,
+    )?))
+}
+
+async fn help(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    Ok(Some(handle_help(call.message, call.language)?))
+}
+
+async fn add(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let (title, interval_days) = match parse_add_todo(call.args) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let reply = handle_add_todo(
+        title,
+        interval_days,
+        call.message,
+        call.now,
+        call.texts,
+        call.ctx.storage,
+        call.ctx.messenger,
+    )
+    .await?;
+
+    Ok(Some(reply))
+}
+
+async fn todos(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let today = call.now.date_naive();
+    let reply = handle_list_todos(call.message, today, call.texts, call.ctx.storage).await?;
+
+    Ok(Some(reply))
+}
+
+async fn board(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let today = call.now.date_naive();
+    handle_board(
+        call.message,
+        today,
+        call.texts,
+        call.ctx.storage,
+        call.ctx.messenger,
+    )
+    .await?;
+
+    Ok(None)
+}
+
+async fn my_todos(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let today = call.now.date_naive();
+    let reply = handle_my_todos(call.message, today, call.texts, call.ctx.storage).await?;
+
+    Ok(Some(reply))
+}
+
+async fn notify(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let enabled = parse_on_off(call.args);
+    let reply = handle_notify(enabled, call.message, call.texts, call.ctx.storage).await?;
+
+    Ok(Some(reply))
+}
+
+async fn show(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let num = match parse_num(call.args) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let reply = handle_show_todo(
+        num,
+        call.message,
+        call.ctx.config.default_timezone,
+        call.texts,
+        call.ctx.storage,
+    )
+    .await?;
+
+    Ok(Some(reply))
+}
+
+async fn check(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let num = match parse_num(call.args) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let reply = handle_check_todo(
+        num,
+        call.message,
+        call.ctx.clock.now(),
+        call.texts,
+        call.ctx.storage,
+    )
+    .await?;
+
+    Ok(Some(reply))
+}
+
+async fn delete(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let num = match parse_num(call.args) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    if let Some(refusal) = refusal(Operation::Delete, Some(num), &call).await? {
+        return Ok(Some(refusal));
+    }
+
+    let reply =
+        handle_delete_todo(num, call.message, call.now, call.texts, call.ctx.storage).await?;
+
+    Ok(Some(reply))
+}
+
+async fn undo(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let reply = handle_undo(
+        call.message,
+        call.ctx.clock.now(),
+        call.texts,
+        call.ctx.storage,
+    )
+    .await?;
+
+    Ok(Some(reply))
+}
+
+async fn language(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let code = first_word(call.args).map(|v| v.to_string());
+    if code.is_some() {
+        if let Some(refusal) = refusal(Operation::Settings, None, &call).await? {
+            return Ok(Some(refusal));
+        }
+    }
+
+    let reply = handle_language(code, call.message, call.ctx.storage).await?;
+
+    Ok(Some(reply))
+}
+
+async fn permissions(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let mut words = call.args.split_whitespace().map(|v| v.to_string());
+    let (operation, permission) = (words.next(), words.next());
+    if permission.is_some() {
+        if let Some(refusal) = refusal(Operation::Settings, None, &call).await? {
+            return Ok(Some(refusal));
+        }
+    }
+
+    let reply = handle_permissions(
+        operation,
+        permission,
+        call.message,
+        call.texts,
+        call.ctx.storage,
+    )
+    .await?;
+
+    Ok(Some(reply))
+}
+
+async fn assign_creator(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let enabled = parse_on_off(call.args);
+    if enabled.is_some() {
+        if let Some(refusal) = refusal(Operation::Settings, None, &call).await? {
+            return Ok(Some(refusal));
+        }
+    }
+
+    let reply = handle_assign_creator(enabled, call.message, call.texts, call.ctx.storage).await?;
+
+    Ok(Some(reply))
+}
+
+async fn confirm_deletes(call: Call<'_>) -> Result<Option<SendMessageParams>, LeditError> {
+    let enabled = parse_on_off(call.args);
+    if enabled.is_some() {
+        if let Some(refusal) = refusal(Operation::Settings, None, &call).await? {
+            return Ok(Some(refusal));
+        }
+    }
+
+    let reply = handle_confirm_deletes(enabled, call.message, call.texts, call.ctx.storage).await?;
+
+    Ok(Some(reply))
+}
+
+/// The reply refusing the call if the chat restricted `operation` to others than the sender.
+async fn refusal(
+    operation: Operation,
+    todo_num: Option<usize>,
+    call: &Call<'_>,
+) -> Result<Option<SendMessageParams>, LeditError> {
+    check_permission(
+        operation,
+        todo_num,
+        call.message,
+        call.texts,
+        call.ctx.storage,
+        call.ctx.messenger,
+    )
+    .await
+}
+
+/// Title and interval of a todo to add, from arguments like `every 2 days: Clean kitchen`.
+fn parse_add_todo(args: &str) -> Option<(String, Option<usize>)> {
+    let recurring_re = Regex::new(
+        r"\A((?i)every|alle(?-i))[ ]+([0-9]+)[ ]+((?i)day|tag(?-i))(s|e){0,1}:[ ]+([\p{L}0-9\-_:,. ].{0,64})",
+    )
+    .expect("add_recurring_todo_re construction failed");
+    if let Some(caps) = recurring_re.captures(args) {
+        let interval_str = caps
+            .get(2)
+            .expect("recurring_todo_interval_re caps failed")
+            .as_str();
+        let title = caps
+            .get(5)
+            .expect("recurring_todo_title_re caps failed")
+            .as_str()
+            .to_string();
+        return Some((title, interval_str.parse::<usize>().ok()));
+    }
+
+    let one_time_re = Regex::new(r"\A[\p{L}0-9\-_:,. ].{0,64}")
+        .expect("add_one_time_todo_re construction failed");
+    one_time_re
+        .find(args)
+        .map(|title| (title.as_str().to_string(), None))
+}
+
+/// Number of the todo to check or delete, the first one if the argument is no number.
+fn parse_num(args: &str) -> Option<usize> {
+    if args.is_empty() {
+        return None;
+    }
+
+    let num_re = Regex::new(r"\A[0-9]{0,4}").expect("building num_re failed");
+    let num = num_re
+        .find(args)
+        .and_then(|v| v.as_str().parse().ok())
+        .unwrap_or(1);
+
+    Some(num)
+}
+
+fn parse_on_off(args: &str) -> Option<bool> {
+    match first_word(args).map(|v| v.to_lowercase()).as_deref() {
+        Some("on" | "an") => Some(true),
+        Some("off" | "aus") => Some(false),
+        _ => None,
+    }
+}
+
+fn first_word(args: &str) -> Option<&str> {
+    args.split_whitespace().next()
+}
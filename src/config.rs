@@ -0,0 +1,234 @@
+use crate::{error::LeditError, i18n::Language};
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+const DEFAULT_CONFIG_FILE: &str = "./ledit.toml";
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub token: String,
+    pub database_url: String,
+    pub interval_ms: u64,
+    pub pool_max_connections: u32,
+    pub one_time_todo_retention_days: i64,
+    pub default_timezone: Tz,
+    /// Name the bot introduces itself with, `{bot}` in texts.
+    pub bot_name: String,
+    /// Texts read from `TEXT_DIR` at startup, by file name. They replace the embedded defaults.
+    pub text_overrides: HashMap<String, String>,
+    pub reminder_time: Option<NaiveTime>,
+    pub digest_time: Option<NaiveTime>,
+    pub simulation_step_minutes: Option<i64>,
+    /// Confirms that the database may be filled with the future dates of a simulation.
+    pub simulation_throwaway_database: bool,
+}
+
+/// Values read from the optional TOML config file. Every field can be
+/// overridden by the env var of the same name in upper case.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    token: Option<String>,
+    database_url: Option<String>,
+    interval_ms: Option<u64>,
+    pool_max_connections: Option<u32>,
+    one_time_todo_retention_days: Option<i64>,
+    default_timezone: Option<String>,
+    bot_name: Option<String>,
+    text_dir: Option<PathBuf>,
+    reminder_time: Option<String>,
+    digest_time: Option<String>,
+    simulation_step_minutes: Option<i64>,
+    simulation_throwaway_database: Option<bool>,
+}
+
+impl Config {
+    /// Loads the config from `LEDIT_CONFIG` (or `./ledit.toml` if present)
+    /// and env vars, then validates it.
+    pub fn load() -> Result<Self, LeditError> {
+        // like `env::var`, values that are not unicode count as unset
+        let vars = env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+
+        Config::from_vars(&vars)
+    }
+
+    /// Same as [`Config::load`], but takes the env vars from `vars`.
+    pub fn from_vars(vars: &HashMap<String, String>) -> Result<Self, LeditError> {
+        let file = match vars.get("LEDIT_CONFIG") {
+            Some(path) => read_file_config(path)?,
+            None if PathBuf::from(DEFAULT_CONFIG_FILE).is_file() => {
+                read_file_config(DEFAULT_CONFIG_FILE)?
+            }
+            None => FileConfig::default(),
+        };
+
+        let token = from_env(vars, "TOKEN")?
+            .or(file.token)
+            .ok_or_else(|| missing("TOKEN"))?;
+        let database_url = from_env(vars, "DATABASE_URL")?
+            .or(file.database_url)
+            .ok_or_else(|| missing("DATABASE_URL"))?;
+        let interval_ms = from_env(vars, "INTERVAL_MS")?
+            .or(file.interval_ms)
+            .unwrap_or(10000);
+        let pool_max_connections = from_env(vars, "POOL_MAX_CONNECTIONS")?
+            .or(file.pool_max_connections)
+            .unwrap_or(16);
+        let one_time_todo_retention_days = from_env(vars, "ONE_TIME_TODO_RETENTION_DAYS")?
+            .or(file.one_time_todo_retention_days)
+            .unwrap_or(1);
+        let default_timezone = from_env::<String>(vars, "DEFAULT_TIMEZONE")?
+            .or(file.default_timezone)
+            .map(|v| Tz::from_str(&v).map_err(|_| invalid("DEFAULT_TIMEZONE", "unknown timezone")))
+            .transpose()?
+            .unwrap_or(Tz::UTC);
+        let bot_name = from_env(vars, "BOT_NAME")?
+            .or(file.bot_name)
+            .unwrap_or_else(|| "ledit bot".to_string());
+        let text_overrides = match from_env::<PathBuf>(vars, "TEXT_DIR")?.or(file.text_dir) {
+            Some(text_dir) => read_text_overrides(&text_dir)?,
+            None => HashMap::new(),
+        };
+        let reminder_time = from_env::<String>(vars, "REMINDER_TIME")?
+            .or(file.reminder_time)
+            .map(|v| parse_time("REMINDER_TIME", &v))
+            .transpose()?;
+        let digest_time = from_env::<String>(vars, "DIGEST_TIME")?
+            .or(file.digest_time)
+            .map(|v| parse_time("DIGEST_TIME", &v))
+            .transpose()?;
+        let simulation_step_minutes =
+            from_env(vars, "SIMULATION_STEP_MINUTES")?.or(file.simulation_step_minutes);
+        let simulation_throwaway_database = from_env(vars, "SIMULATION_THROWAWAY_DATABASE")?
+            .or(file.simulation_throwaway_database)
+            .unwrap_or(false);
+
+        let config = Config {
+            token,
+            database_url,
+            interval_ms,
+            pool_max_connections,
+            one_time_todo_retention_days,
+            default_timezone,
+            bot_name,
+            text_overrides,
+            reminder_time,
+            digest_time,
+            simulation_step_minutes,
+            simulation_throwaway_database,
+        };
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks the values, the error names the first invalid one.
+    pub fn validate(&self) -> Result<(), LeditError> {
+        if self.token.trim().is_empty() {
+            return Err(invalid("TOKEN", "must not be empty"));
+        }
+        if !(self.database_url.starts_with("postgres://")
+            || self.database_url.starts_with("postgresql://")
+            || self.database_url.starts_with("sqlite:"))
+        {
+            return Err(invalid(
+                "DATABASE_URL",
+                "must be a postgres:// or sqlite:// url",
+            ));
+        }
+        if self.interval_ms < 1000 {
+            return Err(invalid("INTERVAL_MS", "must be at least 1000"));
+        }
+        // a running job holds one connection for its lock and needs another one for its queries
+        if !(2..=100).contains(&self.pool_max_connections) {
+            return Err(invalid("POOL_MAX_CONNECTIONS", "must be between 2 and 100"));
+        }
+        if self.one_time_todo_retention_days < 0 {
+            return Err(invalid(
+                "ONE_TIME_TODO_RETENTION_DAYS",
+                "must not be negative",
+            ));
+        }
+        if matches!(self.simulation_step_minutes, Some(v) if v <= 0) {
+            return Err(invalid("SIMULATION_STEP_MINUTES", "must be positive"));
+        }
+        // a simulation stores future dates, jobs would wait for them after going back to real time
+        if self.simulation_step_minutes.is_some()
+            && !self.database_url.contains(":memory:")
+            && !self.simulation_throwaway_database
+        {
+            return Err(invalid(
+                "SIMULATION_STEP_MINUTES",
+                "needs an in-memory database or SIMULATION_THROWAWAY_DATABASE=true",
+            ));
+        }
+        if self.bot_name.trim().is_empty() {
+            return Err(invalid("BOT_NAME", "must not be empty"));
+        }
+
+        Ok(())
+    }
+}
+
+fn read_file_config(path: &str) -> Result<FileConfig, LeditError> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| LeditError::Config(format!("failed to read `{}`: {}", path, err)))?;
+
+    toml::from_str(&content)
+        .map_err(|err| LeditError::Config(format!("failed to parse `{}`: {}", path, err)))
+}
+
+/// Reads the text files of all languages that exist in `text_dir`.
+fn read_text_overrides(text_dir: &Path) -> Result<HashMap<String, String>, LeditError> {
+    if !text_dir.is_dir() {
+        return Err(invalid("TEXT_DIR", "must be an existing directory"));
+    }
+
+    let mut overrides = HashMap::new();
+    for file in Language::ALL.iter().map(|v| v.texts().start_file) {
+        let path = text_dir.join(file);
+        if path.is_file() {
+            let text = fs::read_to_string(&path).map_err(|err| {
+                LeditError::Config(format!("failed to read `{}`: {}", path.display(), err))
+            })?;
+            overrides.insert(file.to_string(), text);
+        }
+    }
+
+    Ok(overrides)
+}
+
+fn from_env<T: FromStr>(
+    vars: &HashMap<String, String>,
+    key: &str,
+) -> Result<Option<T>, LeditError> {
+    match vars.get(key) {
+        Some(v) => v
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| invalid(key, "failed to parse value")),
+        None => Ok(None),
+    }
+}
+
+fn parse_time(key: &str, value: &str) -> Result<NaiveTime, LeditError> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| invalid(key, "expected a time like 09:30"))
+}
+
+fn missing(key: &str) -> LeditError {
+    LeditError::Config(format!("`{}` is not set", key))
+}
+
+fn invalid(key: &str, reason: &str) -> LeditError {
+    LeditError::Config(format!("invalid value for `{}`: {}", key, reason))
+}
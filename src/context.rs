@@ -0,0 +1,10 @@
+use crate::{clock::Clock, config::Config, messenger::Messenger, storage::Storage};
+
+/// Everything needed to handle messages and run jobs.
+#[derive(Clone, Copy)]
+pub struct Context<'a> {
+    pub storage: &'a dyn Storage,
+    pub config: &'a Config,
+    pub messenger: &'a dyn Messenger,
+    pub clock: &'a dyn Clock,
+}
@@ -1,4 +1,8 @@
-use frankenstein::api_params::SendMessageParamsBuilderError;
+use frankenstein::api_params::{
+    AnswerCallbackQueryParamsBuilderError, EditMessageTextParamsBuilderError,
+    GetChatAdministratorsParamsBuilderError, PinChatMessageParamsBuilderError,
+    SendMessageParamsBuilderError, SetMyCommandsParamsBuilderError,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,9 +10,27 @@ pub enum LeditError {
     #[error("Frankenstein Error: {0}")]
     SendMessageParamsBuilder(#[from] SendMessageParamsBuilderError),
 
-    #[error("Failed to find random user")]
+    #[error("Frankenstein Error: {0}")]
+    EditMessageTextParamsBuilder(#[from] EditMessageTextParamsBuilderError),
+
+    #[error("Frankenstein Error: {0}")]
+    PinChatMessageParamsBuilder(#[from] PinChatMessageParamsBuilderError),
+
+    #[error("Frankenstein Error: {0}")]
+    AnswerCallbackQueryParamsBuilder(#[from] AnswerCallbackQueryParamsBuilderError),
+
+    #[error("Frankenstein Error: {0}")]
+    GetChatAdministratorsParamsBuilder(#[from] GetChatAdministratorsParamsBuilderError),
+
+    #[error("Frankenstein Error: {0}")]
+    SetMyCommandsParamsBuilder(#[from] SetMyCommandsParamsBuilderError),
+
+    #[error("Telegram Api Error: {0}")]
     Frankenstein(String),
 
+    #[error("Telegram rejected the request ({code}): {description}")]
+    TelegramRejected { code: u64, description: String },
+
     #[error("Sqlx Error: {0}")]
     Sqlx(#[from] sqlx::Error),
 
@@ -17,10 +39,22 @@ pub enum LeditError {
 
     #[error("Failed to find random user")]
     RndUser,
+
+    #[error("Chat member not found")]
+    ChatMemberNotFound,
+
+    #[error("Config Error: {0}")]
+    Config(String),
 }
 
 impl From<frankenstein::Error> for LeditError {
     fn from(err: frankenstein::Error) -> Self {
-        LeditError::Frankenstein(format!("{:?}", err))
+        match err {
+            frankenstein::Error::ApiError(response) => LeditError::TelegramRejected {
+                code: response.error_code,
+                description: response.description,
+            },
+            err => LeditError::Frankenstein(format!("{:?}", err)),
+        }
     }
 }
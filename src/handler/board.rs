@@ -0,0 +1,112 @@
+use crate::{
+    error::LeditError,
+    handler::language::get_language,
+    i18n::Texts,
+    messenger::{Messenger, MAX_MESSAGE_LENGTH},
+    render,
+    storage::Storage,
+};
+use chrono::NaiveDate;
+use frankenstein::{
+    EditMessageTextParamsBuilder, Message, PinChatMessageParamsBuilder, SendMessageParamsBuilder,
+};
+
+/// Posts and pins a new board for the chat, which replaces the previous one.
+pub async fn handle_board(
+    message: &Message,
+    today: NaiveDate,
+    texts: &Texts,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), LeditError> {
+    let chat_id = message.chat.id;
+    let text = board_text(chat_id, today, texts, storage).await?;
+
+    let board = messenger
+        .send_message(
+            &SendMessageParamsBuilder::default()
+                .chat_id(chat_id)
+                .text(text)
+                .parse_mode("HTML")
+                .build()?,
+        )
+        .await?;
+    storage
+        .set_board_message_id(chat_id, board.message_id)
+        .await?;
+
+    // pinning needs admin rights, the board works without them too
+    let pin_result = messenger
+        .pin_chat_message(
+            &PinChatMessageParamsBuilder::default()
+                .chat_id(chat_id)
+                .message_id(board.message_id)
+                .disable_notification(true)
+                .build()?,
+        )
+        .await;
+    if let Err(err) = pin_result {
+        tracing::warn!("failed to pin board, err: {}", err);
+    }
+
+    Ok(())
+}
+
+/// Updates the board of the chat if it has one. A board whose message was deleted is forgotten.
+pub async fn refresh_board(
+    chat_id: i64,
+    today: NaiveDate,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), LeditError> {
+    let message_id = match storage.get_board_message_id(chat_id).await? {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let texts = get_language(chat_id, storage).await?.texts();
+    let text = board_text(chat_id, today, texts, storage).await?;
+
+    let result = messenger
+        .edit_message_text(
+            &EditMessageTextParamsBuilder::default()
+                .chat_id(chat_id)
+                .message_id(message_id)
+                .text(text)
+                .parse_mode("HTML")
+                .build()?,
+        )
+        .await;
+
+    match result {
+        Err(LeditError::TelegramRejected { description, .. })
+            if description.contains("message is not modified") =>
+        {
+            Ok(())
+        }
+        Err(LeditError::TelegramRejected { description, .. })
+            if description.contains("message to edit not found") =>
+        {
+            tracing::info!("board message of chat {} is gone, forget board", chat_id);
+            storage.delete_board(chat_id).await
+        }
+        result => result,
+    }
+}
+
+async fn board_text(
+    chat_id: i64,
+    today: NaiveDate,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<String, LeditError> {
+    let todos = storage.get_sorted_todos(chat_id).await?;
+    let actionable_todos = storage.get_actionable_todos(chat_id, today).await?;
+
+    Ok(render::board(
+        &todos,
+        &actionable_todos,
+        today,
+        MAX_MESSAGE_LENGTH,
+        texts,
+    ))
+}
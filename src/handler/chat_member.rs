@@ -1,9 +1,8 @@
-use crate::error::LeditError;
-use frankenstein::{objects::User, Message};
+use crate::{error::LeditError, i18n::Texts, storage::Storage};
+use frankenstein::{objects::User, Message, SendMessageParams, SendMessageParamsBuilder};
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Pool, Postgres};
-use tracing::info;
+use sqlx::FromRow;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, FromRow)]
@@ -11,73 +10,126 @@ pub struct ChatMember {
     pub id: Uuid,
     pub telegram_user_id: i64,
     pub chat_id: i64,
-    pub username: String,
+    /// Name shown in lists and replies, derived from the telegram profile.
+    pub display_name: String,
     pub todo_weight: i64,
+
+    pub username: Option<String>,
+    pub first_name: String,
+    pub last_name: Option<String>,
 }
 
+/// Returns the name to show for a telegram user: the username if there is one,
+/// otherwise first and last name.
+pub fn display_name(user: &User) -> String {
+    match user.username.as_deref().map(str::trim) {
+        Some(username) if !username.is_empty() => username.to_string(),
+        _ => {
+            let name = match user.last_name.as_deref() {
+                Some(last_name) => format!("{} {}", user.first_name, last_name),
+                None => user.first_name.clone(),
+            };
+            let name = name.trim();
+
+            if name.is_empty() {
+                format!("user {}", user.id)
+            } else {
+                name.to_string()
+            }
+        }
+    }
+}
+
+/// Registers the sender of `message` as chat member or refreshes their names.
 pub async fn register_chat_member(
     message: &Message,
-    pool: &Pool<Postgres>,
+    storage: &dyn Storage,
 ) -> Result<(), LeditError> {
-    if let Some(User {
-        id,
-        first_name,
-        username,
-        ..
-    }) = message.from.as_ref()
-    {
-        let chat_member = sqlx::query_as!(
-            ChatMember,
-            "select * from chat_members where telegram_user_id = $1 and chat_id = $2",
-            *id as i64,
-            message.chat.id,
-        )
-        .fetch_optional(pool)
-        .await?;
-
-        if chat_member.is_none() {
-            info!("register new chat member");
-
-            sqlx::query_as!(
-                ChatMember,
-                r#"
-                    insert into chat_members (
-                        id,
-                        telegram_user_id,
-                        chat_id,
-                        username
-                    )
-                    values ( $1, $2, $3, $4 )
-                "#,
-                Uuid::new_v4(),
-                *id as i64,
-                message.chat.id,
-                username.clone().unwrap_or_else(|| first_name.to_string()),
-            )
-            .execute(pool)
+    if let Some(user) = message.from.as_ref() {
+        storage
+            .upsert_chat_member(&ChatMember {
+                id: Uuid::new_v4(),
+                telegram_user_id: user.id as i64,
+                chat_id: message.chat.id,
+                display_name: display_name(user),
+                todo_weight: 100,
+
+                username: user.username.clone(),
+                first_name: user.first_name.clone(),
+                last_name: user.last_name.clone(),
+            })
             .await?;
-        }
     }
 
     Ok(())
 }
 
-pub async fn get_random_chat_member(
-    chat_id: i64,
-    pool: &Pool<Postgres>,
-) -> Result<Uuid, LeditError> {
-    let users = sqlx::query!(r#"select id from chat_members where chat_id = $1"#, chat_id)
-        .fetch_all(pool)
-        .await?
-        .into_iter()
-        .map(|v| v.id)
+/// Stores the title of the chat of `message`, private chats have none.
+pub async fn register_chat(message: &Message, storage: &dyn Storage) -> Result<(), LeditError> {
+    if let Some(title) = message.chat.title.as_deref() {
+        storage.set_chat_title(message.chat.id, title).await?;
+    }
+
+    Ok(())
+}
+
+/// Turns assigning todos to the member who created them on or off for the chat, or tells
+/// whether it is on if `enabled` is none.
+pub async fn handle_assign_creator(
+    enabled: Option<bool>,
+    message: &Message,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<SendMessageParams, LeditError> {
+    let enabled = match enabled {
+        Some(enabled) => {
+            storage.set_assign_creator(message.chat.id, enabled).await?;
+            enabled
+        }
+        None => storage.get_assign_creator(message.chat.id).await?,
+    };
+
+    let text = if enabled {
+        texts.assign_creator_on
+    } else {
+        texts.assign_creator_off
+    };
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(text)
+        .parse_mode("HTML")
+        .build()?;
+
+    Ok(send_message_params)
+}
+
+/// The members a todo of `creator` can be assigned to. Without the creator if the chat doesn't
+/// assign creators their own todos, unless nobody else is left.
+pub fn assignable_members(
+    members: &[Uuid],
+    creator: Option<Uuid>,
+    assign_creator: bool,
+) -> Vec<Uuid> {
+    let others = members
+        .iter()
+        .filter(|v| Some(**v) != creator)
+        .cloned()
         .collect::<Vec<Uuid>>();
 
+    if assign_creator || others.is_empty() {
+        members.to_vec()
+    } else {
+        others
+    }
+}
+
+pub fn pick_random_chat_member(users: &[Uuid]) -> Option<Uuid> {
+    if users.is_empty() {
+        return None;
+    }
+
     let mut rng: StdRng = SeedableRng::from_entropy();
     let n = rng.gen_range(0..users.len());
 
-    match users.get(n).cloned() {
-        Some(v) => Ok(v),
-        None => Err(LeditError::RndUser),
-    }
+    users.get(n).cloned()
 }
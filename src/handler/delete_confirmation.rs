@@ -0,0 +1,224 @@
+use crate::{
+    error::LeditError,
+    handler::{
+        board::refresh_board,
+        language::get_language,
+        permission::{check_permission, Operation},
+        todo::{delete_todo, Todo},
+    },
+    i18n::{fill, Texts},
+    messenger::{Messenger, MAX_MESSAGE_LENGTH},
+    render::{self, escape_html},
+    storage::Storage,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+use frankenstein::{
+    objects::{InlineKeyboardButton, InlineKeyboardMarkup},
+    AnswerCallbackQueryParamsBuilder, CallbackQuery, EditMessageTextParamsBuilder, Message,
+    ReplyMarkup, SendMessageParams, SendMessageParamsBuilder,
+};
+use uuid::Uuid;
+
+/// Callback data of the buttons answering whether to delete a todo, followed by the id of the
+/// todo, the unix time it was asked at and `yes` or `no`.
+pub const DELETE_CALLBACK_PREFIX: &str = "delete:";
+
+/// How long the buttons of the question can be pressed.
+pub const CONFIRM_DELETE_MINUTES: i64 = 5;
+
+/// Whether deleting `todo` needs a confirmation. Recurring todos always do, as recreating them
+/// loses their schedule.
+pub async fn needs_confirmation(todo: &Todo, storage: &dyn Storage) -> Result<bool, LeditError> {
+    if todo.interval_days.is_some() {
+        return Ok(true);
+    }
+
+    storage.get_confirm_deletes(todo.chat_id).await
+}
+
+/// Asks whether to delete `todo`, with buttons to answer.
+pub fn ask_to_delete(
+    todo: &Todo,
+    message: &Message,
+    now: DateTime<Utc>,
+    texts: &Texts,
+) -> Result<SendMessageParams, LeditError> {
+    let button = |text: &str, answer: &str| InlineKeyboardButton {
+        text: text.to_string(),
+        url: None,
+        login_url: None,
+        callback_data: Some(format!(
+            "{}{}:{}:{}",
+            DELETE_CALLBACK_PREFIX,
+            todo.id,
+            now.timestamp(),
+            answer
+        )),
+        switch_inline_query: None,
+        switch_inline_query_current_chat: None,
+        callback_game: None,
+        pay: None,
+    };
+
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(render::delete_question(todo, texts))
+        .parse_mode("HTML")
+        .reply_markup(ReplyMarkup::InlineKeyboardMarkup(InlineKeyboardMarkup {
+            inline_keyboard: vec![vec![
+                button(texts.delete_yes, "yes"),
+                button(texts.delete_no, "no"),
+            ]],
+        }))
+        .build()?;
+
+    Ok(send_message_params)
+}
+
+/// Deletes or keeps the todo of a pressed button below the question whether to delete it, if
+/// the question has not expired and the presser may delete the todo.
+pub async fn handle_delete_callback(
+    query: &CallbackQuery,
+    now: DateTime<Tz>,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), LeditError> {
+    let message = match query.message.as_ref() {
+        Some(message) => message,
+        None => return Ok(()),
+    };
+    let texts = get_language(message.chat.id, storage).await?.texts();
+
+    let mut parts = query
+        .data
+        .as_deref()
+        .and_then(|v| v.strip_prefix(DELETE_CALLBACK_PREFIX))
+        .unwrap_or_default()
+        .split(':');
+    let todo_id = parts.next().and_then(|v| Uuid::parse_str(v).ok());
+    let asked_at = parts
+        .next()
+        .and_then(|v| v.parse().ok())
+        .and_then(|v| Utc.timestamp_opt(v, 0).single());
+    let confirmed = parts.next() == Some("yes");
+
+    let todo = match todo_id {
+        Some(id) => storage
+            .get_todo(id)
+            .await?
+            .filter(|v| v.chat_id == message.chat.id),
+        None => None,
+    };
+    let expired = match asked_at {
+        Some(asked_at) => {
+            now.with_timezone(&Utc) - asked_at > Duration::minutes(CONFIRM_DELETE_MINUTES)
+        }
+        None => true,
+    };
+
+    let (answer, text) = match todo {
+        None => (
+            texts.todo_not_found.to_string(),
+            Some(texts.todo_not_found.to_string()),
+        ),
+        Some(_) if expired => (
+            texts.delete_expired.to_string(),
+            Some(texts.delete_expired.to_string()),
+        ),
+        Some(todo) => {
+            // the press counts as if its presser sent the delete command
+            let mut request = message.clone();
+            request.from = Some(query.from.clone());
+            let todos = storage.get_sorted_todos(todo.chat_id).await?;
+            let num = todos.iter().position(|v| v.id == todo.id).map(|v| v + 1);
+
+            // both answers are up to those who may delete the todo
+            match check_permission(Operation::Delete, num, &request, texts, storage, messenger)
+                .await?
+            {
+                // the question stays for those who may answer it
+                Some(refusal) => (refusal.text, None),
+                None if !confirmed => {
+                    let text = fill(texts.delete_kept, &[("todo", &todo.description)]);
+                    let html = fill(
+                        texts.delete_kept,
+                        &[("todo", &escape_html(&todo.description))],
+                    );
+                    (text, Some(html))
+                }
+                None => {
+                    let text = delete_todo(&todo, now, texts, storage).await?;
+                    if let Err(err) =
+                        refresh_board(todo.chat_id, now.date_naive(), storage, messenger).await
+                    {
+                        tracing::error!("failed to refresh board, err: {}", err);
+                    }
+
+                    // the remaining todos may not fit into a single message
+                    (
+                        fill(texts.deleted_todo, &[("todo", &todo.description)]),
+                        Some(render::truncate_message(&text, MAX_MESSAGE_LENGTH)),
+                    )
+                }
+            }
+        }
+    };
+
+    messenger
+        .answer_callback_query(
+            &AnswerCallbackQueryParamsBuilder::default()
+                .callback_query_id(query.id.clone())
+                .text(answer)
+                .build()?,
+        )
+        .await?;
+
+    // replacing the question removes its buttons
+    if let Some(text) = text {
+        messenger
+            .edit_message_text(
+                &EditMessageTextParamsBuilder::default()
+                    .chat_id(message.chat.id)
+                    .message_id(message.message_id)
+                    .text(text)
+                    .parse_mode("HTML")
+                    .build()?,
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Turns asking for confirmation before deleting any todo on or off for the chat, or tells
+/// whether it is on if `enabled` is none.
+pub async fn handle_confirm_deletes(
+    enabled: Option<bool>,
+    message: &Message,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<SendMessageParams, LeditError> {
+    let enabled = match enabled {
+        Some(enabled) => {
+            storage
+                .set_confirm_deletes(message.chat.id, enabled)
+                .await?;
+            enabled
+        }
+        None => storage.get_confirm_deletes(message.chat.id).await?,
+    };
+
+    let text = if enabled {
+        texts.confirm_deletes_on
+    } else {
+        texts.confirm_deletes_off
+    };
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(text)
+        .parse_mode("HTML")
+        .build()?;
+
+    Ok(send_message_params)
+}
@@ -1,26 +1,44 @@
-use crate::error::LeditError;
+use crate::{
+    command::COMMANDS,
+    config::Config,
+    error::LeditError,
+    i18n::{fill, Language, Texts},
+    render,
+};
 use frankenstein::{Message, SendMessageParams, SendMessageParamsBuilder};
-use std::fs::read_to_string;
-
-pub fn handle_help(message: &Message) -> Result<SendMessageParams, LeditError> {
-    let help_text = read_to_string("./txt/help.txt")
-        .unwrap_or_else(|_| "Information not available. Sorry 😔".to_string());
 
+pub fn handle_help(message: &Message, language: Language) -> Result<SendMessageParams, LeditError> {
     let send_message_params = SendMessageParamsBuilder::default()
         .chat_id(message.chat.id)
-        .text(&help_text)
+        .text(render::help(COMMANDS, language))
+        .parse_mode("HTML")
         .build()?;
 
     Ok(send_message_params)
 }
 
-pub fn handle_start(message: &Message) -> Result<SendMessageParams, LeditError> {
-    let start_text = read_to_string("./txt/start.txt")
-        .unwrap_or_else(|_| "Information not available. Sorry 😔".to_string());
+pub fn handle_start(
+    message: &Message,
+    config: &Config,
+    texts: &Texts,
+) -> Result<SendMessageParams, LeditError> {
+    let template = config
+        .text_overrides
+        .get(texts.start_file)
+        .map(String::as_str)
+        .unwrap_or(texts.start);
+    // groups have a title, private chats the name of the user
+    let chat_name = message
+        .chat
+        .title
+        .as_deref()
+        .or(message.chat.first_name.as_deref())
+        .unwrap_or_default();
+    let start_text = fill(template, &[("bot", &config.bot_name), ("chat", chat_name)]);
 
     let send_message_params = SendMessageParamsBuilder::default()
         .chat_id(message.chat.id)
-        .text(&start_text)
+        .text(start_text)
         .build()?;
 
     Ok(send_message_params)
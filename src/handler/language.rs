@@ -0,0 +1,56 @@
+use crate::{
+    error::LeditError,
+    i18n::{fill, Language},
+    storage::Storage,
+};
+use frankenstein::{Message, SendMessageParams, SendMessageParamsBuilder};
+use itertools::Itertools;
+
+/// Returns the language the chat chose, english if it chose none.
+pub async fn get_language(chat_id: i64, storage: &dyn Storage) -> Result<Language, LeditError> {
+    let language = storage
+        .get_chat_language(chat_id)
+        .await?
+        .and_then(|v| Language::from_code(&v))
+        .unwrap_or_default();
+
+    Ok(language)
+}
+
+/// Switches the chat to the language with the `code`, or tells the current language and
+/// the available ones if the code is none or unknown.
+pub async fn handle_language(
+    code: Option<String>,
+    message: &Message,
+    storage: &dyn Storage,
+) -> Result<SendMessageParams, LeditError> {
+    let text = match code.as_deref().and_then(Language::from_code) {
+        Some(language) => {
+            storage
+                .set_chat_language(message.chat.id, language.code())
+                .await?;
+
+            fill(
+                language.texts().language_set,
+                &[("language", &language.to_string())],
+            )
+        }
+        None => {
+            let language = get_language(message.chat.id, storage).await?;
+            let codes = Language::ALL.iter().map(|v| v.code()).join(", ");
+
+            fill(
+                language.texts().language_current,
+                &[("language", &language.to_string()), ("codes", &codes)],
+            )
+        }
+    };
+
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(text)
+        .parse_mode("HTML")
+        .build()?;
+
+    Ok(send_message_params)
+}
@@ -1,3 +1,10 @@
+pub mod board;
 pub mod chat_member;
+pub mod delete_confirmation;
 pub mod info;
+pub mod language;
+pub mod my_todos;
+pub mod notification;
+pub mod permission;
 pub mod todo;
+pub mod undo;
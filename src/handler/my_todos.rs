@@ -0,0 +1,194 @@
+use crate::{
+    error::LeditError,
+    handler::{
+        board::refresh_board,
+        language::get_language,
+        undo::{record_undo, UndoAction},
+    },
+    i18n::Texts,
+    messenger::{Messenger, MAX_MESSAGE_LENGTH},
+    render,
+    storage::{Storage, UserTodo},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use frankenstein::{
+    objects::{ChatType, InlineKeyboardButton, InlineKeyboardMarkup},
+    AnswerCallbackQueryParamsBuilder, CallbackQuery, EditMessageTextParamsBuilder, Message,
+    ReplyMarkup, SendMessageParams, SendMessageParamsBuilder,
+};
+use uuid::Uuid;
+
+/// Callback data of the button checking a todo, followed by the id of the todo.
+const CHECK_CALLBACK_PREFIX: &str = "check:";
+
+/// Lists the todos of the sender in all chats, with a button to check each of them.
+pub async fn handle_my_todos(
+    message: &Message,
+    today: NaiveDate,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<SendMessageParams, LeditError> {
+    let mut send_message_params_builder = SendMessageParamsBuilder::default();
+    send_message_params_builder
+        .chat_id(message.chat.id)
+        .parse_mode("HTML");
+
+    // the list would reveal the todos of other groups
+    if message.chat.type_field != ChatType::Private {
+        return Ok(send_message_params_builder
+            .text(texts.my_todos_in_private_chat)
+            .build()?);
+    }
+
+    let user = match message.from.as_ref() {
+        Some(user) => user,
+        None => {
+            return Ok(send_message_params_builder
+                .text(texts.unknown_user)
+                .build()?)
+        }
+    };
+
+    let todos = storage.get_todos_of_user(user.id as i64, today).await?;
+    send_message_params_builder.text(render::truncate_message(
+        &render::my_todos(&todos, today, texts),
+        MAX_MESSAGE_LENGTH,
+    ));
+    if !todos.is_empty() {
+        send_message_params_builder.reply_markup(ReplyMarkup::InlineKeyboardMarkup(check_buttons(
+            &todos, texts,
+        )));
+    }
+
+    Ok(send_message_params_builder.build()?)
+}
+
+/// Checks or unchecks the todo of a pressed button, if the presser is a member of its chat,
+/// and updates the list the button belongs to.
+pub async fn handle_check_callback(
+    query: &CallbackQuery,
+    now: DateTime<Tz>,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), LeditError> {
+    let today = now.date_naive();
+    let todo_id = query
+        .data
+        .as_deref()
+        .and_then(|v| v.strip_prefix(CHECK_CALLBACK_PREFIX))
+        .and_then(|v| Uuid::parse_str(v).ok());
+    // the list is in the private chat of the presser
+    let texts = get_language(query.from.id as i64, storage).await?.texts();
+
+    let todo = match todo_id {
+        Some(id) => storage.get_todo(id).await?,
+        None => None,
+    };
+    let chat_member = match todo.as_ref() {
+        Some(todo) => {
+            storage
+                .get_chat_member(query.from.id as i64, todo.chat_id)
+                .await?
+        }
+        None => None,
+    };
+
+    let answer = match (todo, chat_member) {
+        (Some(mut todo), Some(chat_member)) => {
+            record_undo(UndoAction::Check, &todo, now.with_timezone(&Utc), storage).await?;
+            todo.done_by = if todo.done_by.is_some() {
+                None
+            } else {
+                Some(chat_member.id)
+            };
+            storage.set_todo_done_by(todo.id, todo.done_by).await?;
+
+            if let Err(err) = refresh_board(todo.chat_id, today, storage, messenger).await {
+                tracing::error!("failed to refresh board, err: {}", err);
+            }
+
+            render::checked_todo_notice(&todo, texts)
+        }
+        _ => texts.todo_not_found.to_string(),
+    };
+
+    messenger
+        .answer_callback_query(
+            &AnswerCallbackQueryParamsBuilder::default()
+                .callback_query_id(query.id.clone())
+                .text(answer)
+                .build()?,
+        )
+        .await?;
+
+    if let Some(message) = query.message.as_ref() {
+        refresh_my_todos(
+            message,
+            query.from.id as i64,
+            today,
+            texts,
+            storage,
+            messenger,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn refresh_my_todos(
+    message: &Message,
+    telegram_user_id: i64,
+    today: NaiveDate,
+    texts: &Texts,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), LeditError> {
+    let todos = storage.get_todos_of_user(telegram_user_id, today).await?;
+
+    let mut edit_message_text_params_builder = EditMessageTextParamsBuilder::default();
+    edit_message_text_params_builder
+        .chat_id(message.chat.id)
+        .message_id(message.message_id)
+        .text(render::truncate_message(
+            &render::my_todos(&todos, today, texts),
+            MAX_MESSAGE_LENGTH,
+        ))
+        .parse_mode("HTML");
+    if !todos.is_empty() {
+        edit_message_text_params_builder.reply_markup(check_buttons(&todos, texts));
+    }
+
+    match messenger
+        .edit_message_text(&edit_message_text_params_builder.build()?)
+        .await
+    {
+        Err(LeditError::TelegramRejected { description, .. })
+            if description.contains("message is not modified") =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn check_buttons(todos: &[UserTodo], texts: &Texts) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup {
+        inline_keyboard: todos
+            .iter()
+            .map(|todo| {
+                vec![InlineKeyboardButton {
+                    text: render::my_todo_button(todo, texts),
+                    url: None,
+                    login_url: None,
+                    callback_data: Some(format!("{}{}", CHECK_CALLBACK_PREFIX, todo.id)),
+                    switch_inline_query: None,
+                    switch_inline_query_current_chat: None,
+                    callback_game: None,
+                    pay: None,
+                }]
+            })
+            .collect(),
+    }
+}
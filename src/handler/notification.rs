@@ -0,0 +1,149 @@
+use crate::{
+    error::LeditError, handler::language::get_language, i18n::Texts, messenger::Messenger, render,
+    storage::Storage,
+};
+use frankenstein::{objects::ChatType, Message, SendMessageParams, SendMessageParamsBuilder};
+use uuid::Uuid;
+
+/// Turns direct messages about assigned todos on or off for the sender, or tells whether they
+/// are on if `enabled` is none.
+pub async fn handle_notify(
+    enabled: Option<bool>,
+    message: &Message,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<SendMessageParams, LeditError> {
+    let mut send_message_params_builder = SendMessageParamsBuilder::default();
+    send_message_params_builder
+        .chat_id(message.chat.id)
+        .parse_mode("HTML");
+
+    // telegram only lets bots message users who started a chat with them
+    if message.chat.type_field != ChatType::Private {
+        return Ok(send_message_params_builder
+            .text(texts.notify_in_private_chat)
+            .build()?);
+    }
+
+    let user = match message.from.as_ref() {
+        Some(user) => user,
+        None => {
+            return Ok(send_message_params_builder
+                .text(texts.unknown_user)
+                .build()?)
+        }
+    };
+
+    let enabled = match enabled {
+        Some(enabled) => {
+            storage.set_notifications(user.id as i64, enabled).await?;
+            enabled
+        }
+        None => storage.get_notifications(user.id as i64).await?,
+    };
+
+    let text = if enabled {
+        texts.notifications_on
+    } else {
+        texts.notifications_off
+    };
+
+    Ok(send_message_params_builder.text(text).build()?)
+}
+
+/// Sends a notice about the todo `description` of the chat `chat_id` to the chat member in a
+/// private chat, if they turned notifications on. `notice` picks the template from the catalog
+/// of the language of the private chat.
+///
+/// Failures are logged only, a notice must not fail the action it is about. Notifications of
+/// users who blocked the bot or deleted their chat with it are turned off.
+pub async fn notify_chat_member(
+    chat_member_id: Uuid,
+    chat_id: i64,
+    description: &str,
+    notice: fn(&Texts) -> &'static str,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) {
+    let result = try_notify_chat_member(
+        chat_member_id,
+        chat_id,
+        description,
+        notice,
+        storage,
+        messenger,
+    )
+    .await;
+
+    match result {
+        Ok(()) => {}
+        Err(LeditError::TelegramRejected { code, description })
+            if code == 403 || description.contains("chat not found") =>
+        {
+            tracing::info!(
+                "can't message chat member, turn notifications off: {}",
+                description
+            );
+            if let Err(err) = disable_notifications(chat_member_id, storage).await {
+                tracing::error!("failed to turn notifications off, err: {}", err);
+            }
+        }
+        Err(err) => tracing::error!("failed to notify chat member, err: {}", err),
+    }
+}
+
+async fn try_notify_chat_member(
+    chat_member_id: Uuid,
+    chat_id: i64,
+    description: &str,
+    notice: fn(&Texts) -> &'static str,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), LeditError> {
+    let telegram_user_id = match storage
+        .get_notified_telegram_user_id(chat_member_id)
+        .await?
+    {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+
+    // the todo is in the private chat already
+    if telegram_user_id == chat_id {
+        return Ok(());
+    }
+
+    let texts = get_language(telegram_user_id, storage).await?.texts();
+    let title = storage.get_chat_title(chat_id).await?;
+    let text = render::todo_notice(
+        notice(texts),
+        description,
+        &render::chat_title(chat_id, title.as_deref(), texts),
+    );
+
+    messenger
+        .send_message(
+            &SendMessageParamsBuilder::default()
+                .chat_id(telegram_user_id)
+                .text(text)
+                .parse_mode("HTML")
+                .build()?,
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn disable_notifications(
+    chat_member_id: Uuid,
+    storage: &dyn Storage,
+) -> Result<(), LeditError> {
+    if let Some(telegram_user_id) = storage
+        .get_notified_telegram_user_id(chat_member_id)
+        .await?
+    {
+        storage.set_notifications(telegram_user_id, false).await?;
+    }
+
+    Ok(())
+}
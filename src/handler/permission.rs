@@ -0,0 +1,232 @@
+use crate::{
+    error::LeditError,
+    i18n::{fill, Texts},
+    messenger::Messenger,
+    render,
+    storage::Storage,
+};
+use frankenstein::{
+    objects::ChatType, GetChatAdministratorsParamsBuilder, Message, SendMessageParams,
+    SendMessageParamsBuilder,
+};
+
+/// Operations a chat can restrict to some of its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Delete,
+    /// Changing the settings of the chat, like its language or permissions.
+    Settings,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 2] = [Operation::Delete, Operation::Settings];
+
+    /// The code stored in the chat permissions and used with `/permissions`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Operation::Delete => "delete",
+            Operation::Settings => "settings",
+        }
+    }
+
+    /// Parses the code or its german alias.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "delete" | "loeschen" | "löschen" => Some(Operation::Delete),
+            "settings" | "einstellungen" => Some(Operation::Settings),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self, texts: &Texts) -> &'static str {
+        match self {
+            Operation::Delete => texts.operation_delete,
+            Operation::Settings => texts.operation_settings,
+        }
+    }
+}
+
+/// Who may perform an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Permission {
+    #[default]
+    Everyone,
+    /// Telegram admins of the chat, including its owner.
+    Admins,
+    /// Whoever added the todo and the admins of the chat.
+    Creator,
+}
+
+impl Permission {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Permission::Everyone => "everyone",
+            Permission::Admins => "admins",
+            Permission::Creator => "creator",
+        }
+    }
+
+    /// Parses the code or its german alias.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "everyone" | "alle" => Some(Permission::Everyone),
+            "admins" => Some(Permission::Admins),
+            "creator" | "ersteller" => Some(Permission::Creator),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self, texts: &Texts) -> &'static str {
+        match self {
+            Permission::Everyone => texts.permission_everyone,
+            Permission::Admins => texts.permission_admins,
+            Permission::Creator => texts.permission_creator,
+        }
+    }
+
+    /// Whether the operation can be restricted to this permission, only operations on a todo
+    /// have a creator.
+    pub fn applies_to(&self, operation: Operation) -> bool {
+        *self != Permission::Creator || operation == Operation::Delete
+    }
+}
+
+/// Returns who may perform the operation in the chat, everyone if the chat chose nobody.
+pub async fn get_permission(
+    chat_id: i64,
+    operation: Operation,
+    storage: &dyn Storage,
+) -> Result<Permission, LeditError> {
+    let permission = storage
+        .get_chat_permission(chat_id, operation.code())
+        .await?
+        .and_then(|v| Permission::from_code(&v))
+        .unwrap_or_default();
+
+    Ok(permission)
+}
+
+/// Whether the sender of the message may perform the operation in its chat, `todo_num` is the
+/// number of the todo in the chat's list the operation is about. Returns the refusal to send if
+/// they may not.
+pub async fn check_permission(
+    operation: Operation,
+    todo_num: Option<usize>,
+    message: &Message,
+    texts: &Texts,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<Option<SendMessageParams>, LeditError> {
+    // a private chat belongs to its user alone
+    if message.chat.type_field == ChatType::Private {
+        return Ok(None);
+    }
+
+    let permission = get_permission(message.chat.id, operation, storage).await?;
+    let (permitted, refusal) = match permission {
+        Permission::Everyone => (true, ""),
+        Permission::Admins => (
+            is_admin(message, messenger).await?,
+            texts.permission_refused_admins,
+        ),
+        Permission::Creator => (
+            is_creator(todo_num, message, storage).await? || is_admin(message, messenger).await?,
+            texts.permission_refused_creator,
+        ),
+    };
+    if permitted {
+        return Ok(None);
+    }
+
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(fill(refusal, &[("operation", operation.name(texts))]))
+        .parse_mode("HTML")
+        .build()?;
+
+    Ok(Some(send_message_params))
+}
+
+async fn is_admin(message: &Message, messenger: &dyn Messenger) -> Result<bool, LeditError> {
+    let user = match message.from.as_ref() {
+        Some(user) => user,
+        None => return Ok(false),
+    };
+
+    let params = GetChatAdministratorsParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .build()?;
+    let admins = messenger.get_chat_administrators(&params).await?;
+
+    Ok(admins.iter().any(|v| v.id == user.id))
+}
+
+/// Whether the sender created the todo. Missing todos count as theirs, so they learn that
+/// the todo doesn't exist.
+async fn is_creator(
+    todo_num: Option<usize>,
+    message: &Message,
+    storage: &dyn Storage,
+) -> Result<bool, LeditError> {
+    let todos = storage.get_sorted_todos(message.chat.id).await?;
+    let todo = match todo_num.and_then(|v| todos.get(v.saturating_sub(1))) {
+        Some(todo) => todo,
+        None => return Ok(true),
+    };
+    let sender = match message.from.as_ref() {
+        Some(user) => {
+            storage
+                .get_chat_member(user.id as i64, message.chat.id)
+                .await?
+        }
+        None => None,
+    };
+
+    Ok(todo.created_by.is_some() && todo.created_by == sender.map(|v| v.id))
+}
+
+/// Lets the `permission` perform the `operation` in the chat, or lists the permissions of the
+/// chat if either of them is none or unknown.
+pub async fn handle_permissions(
+    operation: Option<String>,
+    permission: Option<String>,
+    message: &Message,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<SendMessageParams, LeditError> {
+    let operation = operation.as_deref().and_then(Operation::from_code);
+    let permission = permission.as_deref().and_then(Permission::from_code);
+
+    let text = match (operation, permission) {
+        (Some(operation), Some(permission)) if permission.applies_to(operation) => {
+            storage
+                .set_chat_permission(message.chat.id, operation.code(), permission.code())
+                .await?;
+
+            fill(
+                texts.permission_set,
+                &[
+                    ("operation", operation.name(texts)),
+                    ("permission", permission.name(texts)),
+                ],
+            )
+        }
+        _ => {
+            let mut permissions = Vec::new();
+            for operation in Operation::ALL {
+                let permission = get_permission(message.chat.id, operation, storage).await?;
+                permissions.push((operation, permission));
+            }
+
+            render::permissions(&permissions, texts)
+        }
+    };
+
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(text)
+        .parse_mode("HTML")
+        .build()?;
+
+    Ok(send_message_params)
+}
@@ -1,9 +1,21 @@
-use crate::{error::LeditError, handler::chat_member::get_random_chat_member, util::today};
-use chrono::NaiveDate;
+use crate::{
+    error::LeditError,
+    handler::{
+        chat_member::{assignable_members, pick_random_chat_member},
+        delete_confirmation::{ask_to_delete, needs_confirmation},
+        notification::notify_chat_member,
+        undo::{record_undo, UndoAction},
+    },
+    i18n::Texts,
+    messenger::Messenger,
+    render,
+    storage::Storage,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use frankenstein::{objects::User, Message, SendMessageParams, SendMessageParamsBuilder};
-use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Pool, Postgres};
+use sqlx::FromRow;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, FromRow)]
@@ -16,12 +28,20 @@ pub struct Todo {
     pub assigned_user: Uuid,
     pub scheduled_for: NaiveDate,
     pub done_by: Option<Uuid>,
+
+    /// Chat member who added the todo, unknown for todos added before it was recorded.
+    pub created_by: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
 }
+
 pub async fn handle_add_todo(
     title: String,
     mut interval_days: Option<usize>,
     message: &Message,
-    pool: &Pool<Postgres>,
+    now: DateTime<Tz>,
+    texts: &Texts,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
 ) -> Result<SendMessageParams, LeditError> {
     if let Some(v) = interval_days {
         if !(1..=999).contains(&v) {
@@ -29,35 +49,54 @@ pub async fn handle_add_todo(
         }
     };
 
-    let assigned_user = get_random_chat_member(message.chat.id, pool).await?;
-
-    let todo = sqlx::query_as!(
-        Todo,
-        r#"
-            insert into todos (
-                id,
-                chat_id,
-                description,
-                
-                interval_days,
-                assigned_user
-
-            )
-            values ( $1, $2, $3, $4, $5 )
-            RETURNING *
-        "#,
-        Uuid::new_v4(),
-        message.chat.id,
-        &title,
-        interval_days.map(|v| v as i64),
-        assigned_user,
-    )
-    .fetch_one(pool)
-    .await?;
+    let creator = match message.from.as_ref() {
+        Some(user) => {
+            storage
+                .get_chat_member(user.id as i64, message.chat.id)
+                .await?
+        }
+        None => None,
+    }
+    .map(|v| v.id);
+    let members = storage.get_chat_member_ids(message.chat.id).await?;
+    let assign_creator = storage.get_assign_creator(message.chat.id).await?;
+    let assigned_user =
+        pick_random_chat_member(&assignable_members(&members, creator, assign_creator))
+            .ok_or(LeditError::RndUser)?;
+
+    let todo = storage
+        .insert_todo(&Todo {
+            id: Uuid::new_v4(),
+            chat_id: message.chat.id,
+            description: title,
+
+            interval_days: interval_days.map(|v| v as i64),
+            assigned_user,
+            scheduled_for: now.date_naive(),
+            done_by: None,
+
+            created_by: creator,
+            created_at: Some(now.with_timezone(&Utc)),
+        })
+        .await?;
+
+    // the sender reads the reply anyway
+    if creator != Some(todo.assigned_user) {
+        notify_chat_member(
+            todo.assigned_user,
+            todo.chat_id,
+            &todo.description,
+            |v| v.new_todo_notice,
+            storage,
+            messenger,
+        )
+        .await;
+    }
 
     let send_message_params = SendMessageParamsBuilder::default()
         .chat_id(message.chat.id)
-        .text(format!("Added: {}", todo.description))
+        .text(render::added_todo(&todo, texts))
+        .parse_mode("HTML")
         .build()?;
 
     Ok(send_message_params)
@@ -65,109 +104,141 @@ pub async fn handle_add_todo(
 
 pub async fn handle_list_todos(
     message: &Message,
-    pool: &Pool<Postgres>,
+    today: NaiveDate,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<SendMessageParams, LeditError> {
+    let todos = storage.get_sorted_todos(message.chat.id).await?;
+    let actionable_todos = storage.get_actionable_todos(message.chat.id, today).await?;
+    let text = render::todo_overview(&todos, &actionable_todos, today, texts);
+
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(text)
+        .parse_mode("HTML")
+        .build()?;
+
+    Ok(send_message_params)
+}
+
+/// Shows everything about todo number `num`, including who added it.
+pub async fn handle_show_todo(
+    num: usize,
+    message: &Message,
+    timezone: Tz,
+    texts: &Texts,
+    storage: &dyn Storage,
 ) -> Result<SendMessageParams, LeditError> {
-    let mut text = get_all_todos_as_string(message, pool).await?;
+    let todos = storage.get_sorted_todos(message.chat.id).await?;
 
-    text.push_str("\n\n\n");
-    text.push_str(&get_todos_by_username_as_string(message.chat.id, pool).await?);
+    let text = match todos.get(num.saturating_sub(1)) {
+        Some(todo) => {
+            let members = storage.get_chat_members(message.chat.id).await?;
+            let created_on = todo
+                .created_at
+                .map(|v| v.with_timezone(&timezone).date_naive());
+
+            render::todo_details(todo, &members, created_on, texts)
+        }
+        None => texts.todo_not_found.to_string(),
+    };
 
     let send_message_params = SendMessageParamsBuilder::default()
         .chat_id(message.chat.id)
         .text(text)
+        .parse_mode("HTML")
         .build()?;
 
     Ok(send_message_params)
 }
 
+/// Deletes todo number `num`, or asks whether to if it needs a confirmation.
 pub async fn handle_delete_todo(
     num: usize,
     message: &Message,
-    pool: &Pool<Postgres>,
+    now: DateTime<Tz>,
+    texts: &Texts,
+    storage: &dyn Storage,
 ) -> Result<SendMessageParams, LeditError> {
-    let todos = get_sorted_todos(message.chat.id, pool).await?;
-    let todo_to_delete = todos.get(num.saturating_sub(1));
-
-    if let Some(todo_to_delete) = todo_to_delete {
-        sqlx::query!(
-            r#"
-                delete from todos where id = $1
-            "#,
-            todo_to_delete.id
-        )
-        .execute(pool)
-        .await?;
+    let todos = storage.get_sorted_todos(message.chat.id).await?;
 
-        let mut text = format!("Deleted: {}\n\n", todo_to_delete.description);
-        text.push_str(&get_all_todos_as_string(message, pool).await?);
+    let text = match todos.get(num.saturating_sub(1)) {
+        Some(todo) if needs_confirmation(todo, storage).await? => {
+            return ask_to_delete(todo, message, now.with_timezone(&Utc), texts);
+        }
+        Some(todo) => delete_todo(todo, now, texts, storage).await?,
+        None => texts.todo_not_found.to_string(),
+    };
 
-        let send_message_params = SendMessageParamsBuilder::default()
-            .chat_id(message.chat.id)
-            .text(text)
-            .build()?;
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(text)
+        .parse_mode("HTML")
+        .build()?;
 
-        Ok(send_message_params)
-    } else {
-        let send_message_params = SendMessageParamsBuilder::default()
-            .chat_id(message.chat.id)
-            .text("Todo not found.")
-            .build()?;
+    Ok(send_message_params)
+}
 
-        Ok(send_message_params)
-    }
+/// Deletes the todo, keeping it for `/undo`, and returns the message listing the remaining
+/// todos of its chat.
+pub async fn delete_todo(
+    todo: &Todo,
+    now: DateTime<Tz>,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<String, LeditError> {
+    record_undo(UndoAction::Delete, todo, now.with_timezone(&Utc), storage).await?;
+    storage.delete_todo(todo.id).await?;
+
+    let remaining = storage.get_sorted_todos(todo.chat_id).await?;
+
+    Ok(render::deleted_todo(
+        todo,
+        &remaining,
+        now.date_naive(),
+        texts,
+    ))
 }
 
 pub async fn handle_check_todo(
     num: usize,
     message: &Message,
-    pool: &Pool<Postgres>,
+    now: DateTime<Utc>,
+    texts: &Texts,
+    storage: &dyn Storage,
 ) -> Result<SendMessageParams, LeditError> {
     if let Some(User { id, .. }) = message.from.as_ref() {
-        let user = sqlx::query!(
-            "select id from chat_members where telegram_user_id = $1 and chat_id = $2",
-            *id as i64,
-            message.chat.id
-        )
-        .fetch_one(pool)
-        .await?;
+        let user = storage
+            .get_chat_member(*id as i64, message.chat.id)
+            .await?
+            .ok_or(LeditError::ChatMemberNotFound)?;
 
-        let todos = get_sorted_todos(message.chat.id, pool).await?;
+        let todos = storage.get_sorted_todos(message.chat.id).await?;
         let todo_to_check = todos.get(num.saturating_sub(1)).cloned();
 
         if let Some(mut todo) = todo_to_check {
+            record_undo(UndoAction::Check, &todo, now, storage).await?;
             todo.done_by = if todo.done_by.is_some() {
                 None
             } else {
                 Some(user.id)
             };
-            sqlx::query!(
-                r#"update todos set done_by = $1 where id = $2"#,
-                todo.done_by,
-                todo.id
-            )
-            .execute(pool)
-            .await?;
-
-            let text = format!(
-                "{} {}",
-                if todo.done_by.is_some() {
-                    "✅"
-                } else {
-                    "☑️"
-                },
-                todo.description
-            );
+            storage.set_todo_done_by(todo.id, todo.done_by).await?;
+
+            let text = render::checked_todo(&todo);
 
             let send_message_params = SendMessageParamsBuilder::default()
                 .chat_id(message.chat.id)
                 .text(text)
+                .parse_mode("HTML")
                 .build()?;
 
             Ok(send_message_params)
         } else {
             let send_message_params = SendMessageParamsBuilder::default()
                 .chat_id(message.chat.id)
-                .text("Todo not found.")
+                .text(texts.todo_not_found)
+                .parse_mode("HTML")
                 .build()?;
 
             Ok(send_message_params)
@@ -175,160 +246,10 @@ pub async fn handle_check_todo(
     } else {
         let send_message_params = SendMessageParamsBuilder::default()
             .chat_id(message.chat.id)
-            .text("Unknown user.")
+            .text(texts.unknown_user)
+            .parse_mode("HTML")
             .build()?;
 
         Ok(send_message_params)
     }
 }
-
-async fn get_sorted_todos(chat_id: i64, pool: &Pool<Postgres>) -> Result<Vec<Todo>, LeditError> {
-    sqlx::query_as!(
-        Todo,
-        r#"
-            select 
-                * 
-            from 
-                todos 
-            where 
-                chat_id = $1 
-            order by 
-                interval_days is null desc, interval_days asc, description asc
-        "#,
-        chat_id,
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|err| err.into())
-}
-
-async fn get_all_todos_as_string(
-    message: &Message,
-    pool: &Pool<Postgres>,
-) -> Result<String, LeditError> {
-    let todos = get_sorted_todos(message.chat.id, pool).await?;
-
-    let mut text = "List of all todos:\n".to_string();
-    let mut n = 1;
-    for todo in &todos {
-        let checkbox = if todo.done_by.is_some() {
-            if todo.scheduled_for == today() {
-                "✅"
-            } else {
-                "🗓"
-            }
-        } else {
-            "☑️"
-        };
-        let recurring = if let Some(interval_days) = todo.interval_days {
-            format!(
-                "(🔄 {} day{})",
-                interval_days,
-                if interval_days > 1 { "s" } else { "" }
-            )
-        } else {
-            "".to_string()
-        };
-        text.push_str(&format!(
-            "\n{} {}. {} {} ",
-            checkbox, n, todo.description, recurring
-        ));
-        n += 1;
-    }
-
-    if todos.is_empty() {
-        text = "No todo found.".to_string();
-    }
-
-    Ok(text)
-}
-
-pub async fn get_todos_by_username_as_string(
-    chat_id: i64,
-    pool: &Pool<Postgres>,
-) -> Result<String, LeditError> {
-    // get actionable todos
-
-    let mut todos_by_username = sqlx::query!(
-        r#"
-            select 
-                t.id,
-                t.chat_id,
-                t.description,
-                t.interval_days,
-                t.assigned_user,
-                t.scheduled_for,
-                t.done_by,
-
-                c.username
-            from 
-                todos as t
-            join 
-                chat_members as c on c.id = t.assigned_user
-            where 
-                t.chat_id = $1
-                and c.chat_id = $1
-                and 
-                    (
-                        (t.interval_days is null and t.scheduled_for <= $2)
-                        or 
-                        (   
-                            t.interval_days is not null
-                            and (
-                                (t.scheduled_for <= $2 and t.done_by is null) 
-                                or (t.scheduled_for = $2 and t.done_by is not null))
-                        )
-                    )
-            order by 
-                t.done_by asc, t.description asc
-        "#,
-        chat_id,
-        today(),
-    )
-    .fetch_all(pool)
-    .await?
-    .into_iter()
-    .into_group_map_by(|v| v.username.clone())
-    .into_iter()
-    .collect::<Vec<(String, _)>>();
-
-    todos_by_username.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap());
-
-    // compose response message
-
-    let text = if todos_by_username.is_empty() {
-        "No todos for today :)".to_string()
-    } else {
-        todos_by_username
-            .into_iter()
-            .map(|(username, todos)| {
-                let mut r: String = format!("Todos for {}:\n", username);
-                if todos.is_empty() {
-                    r.push_str("\nNo todos for today :)")
-                } else {
-                    for todo in todos {
-                        let checkbox = if let Some(done_by) = todo.done_by {
-                            if done_by == todo.assigned_user {
-                                "✅"
-                            } else {
-                                "✅↪️"
-                            }
-                        } else {
-                            "☑️"
-                        };
-                        let delay = if todo.scheduled_for < today() && todo.done_by.is_none() {
-                            "⏳"
-                        } else {
-                            ""
-                        };
-
-                        r.push_str(&format!("\n{}{} {}", checkbox, delay, todo.description));
-                    }
-                }
-                r
-            })
-            .join("\n\n")
-    };
-
-    Ok(text)
-}
@@ -0,0 +1,134 @@
+use crate::{
+    error::LeditError,
+    handler::todo::Todo,
+    i18n::{fill, Texts},
+    render::escape_html,
+    storage::Storage,
+};
+use chrono::{DateTime, Duration, Utc};
+use frankenstein::{Message, SendMessageParams, SendMessageParamsBuilder};
+use uuid::Uuid;
+
+/// How long an action can be undone.
+pub const UNDO_MINUTES: i64 = 10;
+
+/// Actions whose previous state of a todo is kept in the undo journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoAction {
+    Delete,
+    /// Checking or unchecking a todo.
+    Check,
+    /// Assigning a re-scheduled todo to someone else, undoing it only restores the assignee.
+    Reassign,
+}
+
+impl UndoAction {
+    /// The code stored in the undo journal.
+    pub fn code(&self) -> &'static str {
+        match self {
+            UndoAction::Delete => "delete",
+            UndoAction::Check => "check",
+            UndoAction::Reassign => "reassign",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "delete" => Some(UndoAction::Delete),
+            "check" => Some(UndoAction::Check),
+            "reassign" => Some(UndoAction::Reassign),
+            _ => None,
+        }
+    }
+}
+
+/// A todo as it was before `action` changed it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoEntry {
+    pub id: Uuid,
+    pub chat_id: i64,
+    pub action: String,
+    pub todo: Todo,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Keeps `todo` as it is before `action` changes it, so `/undo` can restore it, and forgets
+/// entries that can no longer be undone.
+pub async fn record_undo(
+    action: UndoAction,
+    todo: &Todo,
+    now: DateTime<Utc>,
+    storage: &dyn Storage,
+) -> Result<(), LeditError> {
+    storage
+        .delete_undo_entries_before(now - Duration::minutes(UNDO_MINUTES))
+        .await?;
+
+    storage
+        .insert_undo_entry(&UndoEntry {
+            id: Uuid::new_v4(),
+            chat_id: todo.chat_id,
+            action: action.code().to_string(),
+            todo: todo.clone(),
+            recorded_at: now,
+        })
+        .await
+}
+
+/// Restores the todo changed by the latest undoable action of the chat in the last
+/// [`UNDO_MINUTES`] minutes.
+pub async fn handle_undo(
+    message: &Message,
+    now: DateTime<Utc>,
+    texts: &Texts,
+    storage: &dyn Storage,
+) -> Result<SendMessageParams, LeditError> {
+    let since = now - Duration::minutes(UNDO_MINUTES);
+    let entry = storage.take_last_undo_entry(message.chat.id, since).await?;
+
+    let text = match entry {
+        Some(UndoEntry {
+            action, mut todo, ..
+        }) => {
+            let action = UndoAction::from_code(&action);
+            if action == Some(UndoAction::Reassign) {
+                // the todo stays scheduled, it only goes back to its previous assignee
+                match storage.get_todo(todo.id).await? {
+                    Some(current) => {
+                        todo = Todo {
+                            assigned_user: todo.assigned_user,
+                            ..current
+                        }
+                    }
+                    None => {
+                        return Ok(SendMessageParamsBuilder::default()
+                            .chat_id(message.chat.id)
+                            .text(texts.todo_not_found)
+                            .build()?)
+                    }
+                }
+            }
+
+            storage.restore_todo(&todo).await?;
+
+            let template = match action {
+                Some(UndoAction::Check) => texts.undone_check,
+                Some(UndoAction::Reassign) => texts.undone_reassign,
+                _ => texts.undone_delete,
+            };
+            fill(template, &[("todo", &escape_html(&todo.description))])
+        }
+        None => fill(
+            texts.nothing_to_undo,
+            &[("minutes", &UNDO_MINUTES.to_string())],
+        ),
+    };
+
+    let send_message_params = SendMessageParamsBuilder::default()
+        .chat_id(message.chat.id)
+        .text(text)
+        .parse_mode("HTML")
+        .build()?;
+
+    Ok(send_message_params)
+}
@@ -0,0 +1,464 @@
+//! Message catalogs of the languages a chat can choose with `/language`.
+//!
+//! Texts are templates, `{name}` is replaced by the value of `name` with [`fill`].
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// The code stored in the chat settings and used with `/language`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Language::ALL
+            .into_iter()
+            .find(|v| v.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    pub(crate) fn texts(&self) -> &'static Texts {
+        match self {
+            Language::English => &EN,
+            Language::German => &DE,
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.texts().language_name)
+    }
+}
+
+/// All texts of the bot in one language.
+#[derive(Debug)]
+pub struct Texts {
+    pub language_name: &'static str,
+    /// `chrono` format of dates in listings.
+    pub date_format: &'static str,
+    /// Reply to `/start`, embedded from `txt/`. A file named `start_file` in the text dir
+    /// replaces it. `{bot}` is filled with the bot's name and `{chat}` with the chat's.
+    pub start: &'static str,
+    pub start_file: &'static str,
+
+    pub added_todo: &'static str,
+    pub deleted_todo: &'static str,
+    pub deleting_old_todo: &'static str,
+    pub todo_not_found: &'static str,
+    pub unknown_user: &'static str,
+
+    pub board_title: &'static str,
+    pub todo_list_title: &'static str,
+    pub todo_list_empty: &'static str,
+    pub todos_of_member: &'static str,
+    pub todos_of_today_empty: &'static str,
+    pub interval_day: &'static str,
+    pub interval_days: &'static str,
+    pub next_date: &'static str,
+    pub due_date: &'static str,
+    pub reminder_title: &'static str,
+
+    pub my_todos_title: &'static str,
+    pub my_todos_empty: &'static str,
+    pub my_todos_in_private_chat: &'static str,
+    pub private_chat: &'static str,
+    pub unknown_chat: &'static str,
+    pub checked_todo_notice: &'static str,
+    pub unchecked_todo_notice: &'static str,
+
+    pub notify_in_private_chat: &'static str,
+    pub notifications_on: &'static str,
+    pub notifications_off: &'static str,
+    pub new_todo_notice: &'static str,
+    pub re_scheduled_todo_notice: &'static str,
+    pub overdue_todo_notice: &'static str,
+
+    pub language_set: &'static str,
+    pub language_current: &'static str,
+
+    pub operation_delete: &'static str,
+    pub operation_settings: &'static str,
+    pub permission_everyone: &'static str,
+    pub permission_admins: &'static str,
+    pub permission_creator: &'static str,
+    pub permissions_title: &'static str,
+    pub permissions_usage: &'static str,
+    pub permission_set: &'static str,
+    pub permission_refused_admins: &'static str,
+    pub permission_refused_creator: &'static str,
+
+    pub one_time_todo: &'static str,
+    pub todo_assigned_to: &'static str,
+    pub todo_scheduled_for: &'static str,
+    pub todo_done_by: &'static str,
+    pub todo_open: &'static str,
+    pub todo_created: &'static str,
+    pub todo_created_unknown: &'static str,
+    pub former_member: &'static str,
+    pub assign_creator_on: &'static str,
+    pub assign_creator_off: &'static str,
+    pub undone_delete: &'static str,
+    pub undone_check: &'static str,
+    pub undone_reassign: &'static str,
+    pub nothing_to_undo: &'static str,
+    pub delete_question: &'static str,
+    pub delete_yes: &'static str,
+    pub delete_no: &'static str,
+    pub delete_kept: &'static str,
+    pub delete_expired: &'static str,
+    pub confirm_deletes_on: &'static str,
+    pub confirm_deletes_off: &'static str,
+
+    pub help_command: CommandTexts,
+    pub add_command: CommandTexts,
+    pub todos_command: CommandTexts,
+    pub board_command: CommandTexts,
+    pub my_todos_command: CommandTexts,
+    pub notify_command: CommandTexts,
+    pub check_command: CommandTexts,
+    pub delete_command: CommandTexts,
+    pub language_command: CommandTexts,
+    pub permissions_command: CommandTexts,
+    pub show_command: CommandTexts,
+    pub assign_creator_command: CommandTexts,
+    pub undo_command: CommandTexts,
+    pub confirm_deletes_command: CommandTexts,
+}
+
+/// Texts of a command in the command menu and the help.
+#[derive(Debug)]
+pub struct CommandTexts {
+    /// Short description shown in telegram's command menu.
+    pub description: &'static str,
+    /// Lines of the help as pairs of example arguments and what the command does with them.
+    pub usage: &'static [(&'static str, &'static str)],
+}
+
+pub static EN: Texts = Texts {
+    language_name: "English",
+    date_format: "%b %-d",
+    start: include_str!("../txt/start.txt"),
+    start_file: "start.txt",
+
+    added_todo: "Added: {todo}",
+    deleted_todo: "Deleted: {todo}",
+    deleting_old_todo: "🗑 Deleting old & done todo: {todo}",
+    todo_not_found: "Todo not found.",
+    unknown_user: "Unknown user.",
+
+    board_title: "📌 Todo board",
+    todo_list_title: "List of all todos:",
+    todo_list_empty: "No todo found.",
+    todos_of_member: "Todos for {member}:",
+    todos_of_today_empty: "No todos for today :)",
+    interval_day: "🔄 {n} day",
+    interval_days: "🔄 {n} days",
+    next_date: "next {date}",
+    due_date: "due {date}",
+    reminder_title: "Reminder, these todos are overdue:",
+
+
+    my_todos_title: "Your todos:",
+    my_todos_empty: "No todos for you today :)",
+    my_todos_in_private_chat:
+        "Send /mytodos to me in a private chat to see your todos of all groups.",
+    private_chat: "Private chat",
+    unknown_chat: "Chat {id}",
+    checked_todo_notice: "✅ Done: {todo}",
+    unchecked_todo_notice: "☑️ Open again: {todo}",
+
+    notify_in_private_chat: "Send /notify on to me in a private chat, I can only message you after you started a chat with me.",
+    notifications_on: "🔔 Notifications are on. I message you about todos assigned to you and overdue todos. Send /notify off to turn them off.",
+    notifications_off: "🔕 Notifications are off. Send /notify on to get messages about todos assigned to you and overdue todos.",
+    new_todo_notice: "🆕 New todo for you in <b>{chat}</b>: {todo}",
+    re_scheduled_todo_notice: "🔄 Your turn in <b>{chat}</b>: {todo}",
+    overdue_todo_notice: "⏳ Overdue in <b>{chat}</b>: <b>{todo}</b>",
+
+    language_set: "🌍 This chat speaks {language} now.",
+    language_current: "🌍 This chat speaks {language}. Available languages: {codes}",
+
+    operation_delete: "delete todos",
+    operation_settings: "change the settings",
+    permission_everyone: "everyone",
+    permission_admins: "admins",
+    permission_creator: "whoever added the todo and admins",
+    permissions_title: "🔐 Permissions of this chat:",
+    permissions_usage: "Change them with /permissions delete admins, /permissions delete creator or /permissions settings everyone.",
+    permission_set: "🔐 From now on {permission} can {operation}.",
+    permission_refused_admins: "⛔ Only admins of this chat can {operation}.",
+    permission_refused_creator: "⛔ Only whoever added the todo and admins of this chat can {operation}.",
+
+    one_time_todo: "1️⃣ One-time todo",
+    todo_assigned_to: "👤 Assigned to {member}",
+    todo_scheduled_for: "📅 Scheduled for {date}",
+    todo_done_by: "✅ Done by {member}",
+    todo_open: "☑️ Open",
+    todo_created: "✍️ Added by {member} on {date}",
+    todo_created_unknown: "✍️ Added before the bot recorded who adds todos",
+    former_member: "a former member",
+    assign_creator_on: "🎲 Todos can be assigned to whoever added them. Send /assigncreator off to change that.",
+    assign_creator_off: "🎲 Todos are not assigned to whoever added them, unless nobody else is left. Send /assigncreator on to change that.",
+    undone_delete: "↩️ Restored: {todo}",
+    undone_check: "↩️ Undid checking: {todo}",
+    undone_reassign: "↩️ Assigned back to the previous member: {todo}",
+    nothing_to_undo: "Nothing to undo. Deleting, checking and re-assigning todos can be undone for {minutes} minutes.",
+    delete_question: "🗑 Delete {todo}?",
+    delete_yes: "Yes, delete",
+    delete_no: "No, keep it",
+    delete_kept: "Kept: {todo}",
+    delete_expired: "⌛ This question expired, send /delete again.",
+    confirm_deletes_on: "🗑 Deleting any todo asks for confirmation. Send /confirmdeletes off to only confirm deleting recurring todos.",
+    confirm_deletes_off: "🗑 Only deleting recurring todos asks for confirmation. Send /confirmdeletes on to confirm deleting any todo.",
+
+    help_command: CommandTexts {
+        description: "show all commands",
+        usage: &[("", "this message")],
+    },
+    add_command: CommandTexts {
+        description: "add a todo",
+        usage: &[
+            ("<your todo>", "add todo"),
+            ("every 2 days: <your todo>", "add recurring todo"),
+        ],
+    },
+    todos_command: CommandTexts {
+        description: "get a numbered list of todos",
+        usage: &[("", "get a numbered list of todos")],
+    },
+    board_command: CommandTexts {
+        description: "pin a todo list that updates itself",
+        usage: &[("", "pin a todo list that updates itself")],
+    },
+    my_todos_command: CommandTexts {
+        description: "your todos of all groups",
+        usage: &[("", "your todos of all groups, in a private chat with me")],
+    },
+    notify_command: CommandTexts {
+        description: "get messages about your todos",
+        usage: &[("on", "get messages about your todos, in a private chat with me")],
+    },
+    check_command: CommandTexts {
+        description: "(un-)check a todo",
+        usage: &[("1", "(un-)check todo number 1")],
+    },
+    delete_command: CommandTexts {
+        description: "delete a todo",
+        usage: &[("2", "delete todo number 2")],
+    },
+    language_command: CommandTexts {
+        description: "change the language of the chat",
+        usage: &[("de", "auf Deutsch umstellen")],
+    },
+    permissions_command: CommandTexts {
+        description: "who can delete todos and change settings",
+        usage: &[
+            ("", "who can do what in this chat"),
+            ("delete admins", "only admins can delete todos"),
+        ],
+    },
+    show_command: CommandTexts {
+        description: "show the details of a todo",
+        usage: &[("1", "details of todo number 1, like who added it")],
+    },
+    assign_creator_command: CommandTexts {
+        description: "whether todos go to whoever added them",
+        usage: &[("off", "don't assign todos to whoever added them")],
+    },
+    undo_command: CommandTexts {
+        description: "undo deleting, checking or re-assigning a todo",
+        usage: &[("", "undo the last deleting, checking or re-assigning of a todo")],
+    },
+    confirm_deletes_command: CommandTexts {
+        description: "whether deleting any todo needs a confirmation",
+        usage: &[("on", "confirm deleting any todo, not just recurring ones")],
+    },
+};
+
+pub static DE: Texts = Texts {
+    language_name: "Deutsch",
+    date_format: "%d.%m.",
+    start: include_str!("../txt/start.de.txt"),
+    start_file: "start.de.txt",
+
+    added_todo: "Hinzugefügt: {todo}",
+    deleted_todo: "Gelöscht: {todo}",
+    deleting_old_todo: "🗑 Lösche alte & erledigte Aufgabe: {todo}",
+    todo_not_found: "Aufgabe nicht gefunden.",
+    unknown_user: "Unbekannter Nutzer.",
+
+    board_title: "📌 Aufgabentafel",
+    todo_list_title: "Alle Aufgaben:",
+    todo_list_empty: "Keine Aufgabe gefunden.",
+    todos_of_member: "Aufgaben für {member}:",
+    todos_of_today_empty: "Heute keine Aufgaben :)",
+    interval_day: "🔄 {n} Tag",
+    interval_days: "🔄 {n} Tage",
+    next_date: "wieder am {date}",
+    due_date: "fällig am {date}",
+    reminder_title: "Erinnerung, diese Aufgaben sind überfällig:",
+
+
+    my_todos_title: "Deine Aufgaben:",
+    my_todos_empty: "Heute keine Aufgaben für dich :)",
+    my_todos_in_private_chat: "Schick mir /meine in einem privaten Chat, um deine Aufgaben aus allen Gruppen zu sehen.",
+    private_chat: "Privater Chat",
+    unknown_chat: "Chat {id}",
+    checked_todo_notice: "✅ Erledigt: {todo}",
+    unchecked_todo_notice: "☑️ Wieder offen: {todo}",
+
+    notify_in_private_chat: "Schick mir /benachrichtigen an in einem privaten Chat, ich kann dir erst schreiben, nachdem du einen Chat mit mir begonnen hast.",
+    notifications_on: "🔔 Benachrichtigungen sind an. Ich schreibe dir bei Aufgaben, die dir zugeteilt werden, und bei überfälligen Aufgaben. Schick /benachrichtigen aus, um sie abzuschalten.",
+    notifications_off: "🔕 Benachrichtigungen sind aus. Schick /benachrichtigen an, um Nachrichten zu deinen Aufgaben zu bekommen.",
+    new_todo_notice: "🆕 Neue Aufgabe für dich in <b>{chat}</b>: {todo}",
+    re_scheduled_todo_notice: "🔄 Du bist dran in <b>{chat}</b>: {todo}",
+    overdue_todo_notice: "⏳ Überfällig in <b>{chat}</b>: <b>{todo}</b>",
+
+    language_set: "🌍 Dieser Chat spricht jetzt {language}.",
+    language_current: "🌍 Dieser Chat spricht {language}. Verfügbare Sprachen: {codes}",
+
+    operation_delete: "Aufgaben löschen",
+    operation_settings: "die Einstellungen ändern",
+    permission_everyone: "alle",
+    permission_admins: "Admins",
+    permission_creator: "wer die Aufgabe hinzugefügt hat und Admins",
+    permissions_title: "🔐 Rechte in diesem Chat:",
+    permissions_usage: "Ändere sie mit /rechte loeschen admins, /rechte loeschen ersteller oder /rechte einstellungen alle.",
+    permission_set: "🔐 Ab jetzt dürfen {permission} {operation}.",
+    permission_refused_admins: "⛔ Nur Admins dieses Chats dürfen {operation}.",
+    permission_refused_creator: "⛔ Nur wer die Aufgabe hinzugefügt hat und Admins dieses Chats dürfen {operation}.",
+
+    one_time_todo: "1️⃣ Einmalige Aufgabe",
+    todo_assigned_to: "👤 Zugeteilt an {member}",
+    todo_scheduled_for: "📅 Geplant für den {date}",
+    todo_done_by: "✅ Erledigt von {member}",
+    todo_open: "☑️ Offen",
+    todo_created: "✍️ Hinzugefügt von {member} am {date}",
+    todo_created_unknown: "✍️ Hinzugefügt, bevor ich mir gemerkt habe, wer Aufgaben hinzufügt",
+    former_member: "einem ehemaligen Mitglied",
+    assign_creator_on: "🎲 Aufgaben können dem zugeteilt werden, der sie hinzugefügt hat. Schick /erstellerzuteilen aus, um das zu ändern.",
+    assign_creator_off: "🎲 Aufgaben werden nicht dem zugeteilt, der sie hinzugefügt hat, außer es ist niemand anderes da. Schick /erstellerzuteilen an, um das zu ändern.",
+    undone_delete: "↩️ Wiederhergestellt: {todo}",
+    undone_check: "↩️ Abhaken rückgängig gemacht: {todo}",
+    undone_reassign: "↩️ Wieder der vorherigen Person zugeteilt: {todo}",
+    nothing_to_undo: "Nichts rückgängig zu machen. Löschen, Abhaken und neues Zuteilen von Aufgaben lässt sich {minutes} Minuten lang rückgängig machen.",
+    delete_question: "🗑 {todo} löschen?",
+    delete_yes: "Ja, löschen",
+    delete_no: "Nein, behalten",
+    delete_kept: "Behalten: {todo}",
+    delete_expired: "⌛ Diese Frage ist abgelaufen, schick /loeschen noch mal.",
+    confirm_deletes_on: "🗑 Das Löschen jeder Aufgabe muss bestätigt werden. Schick /loeschenbestaetigen aus, um nur das Löschen wiederkehrender Aufgaben zu bestätigen.",
+    confirm_deletes_off: "🗑 Nur das Löschen wiederkehrender Aufgaben muss bestätigt werden. Schick /loeschenbestaetigen an, um das Löschen jeder Aufgabe zu bestätigen.",
+
+    help_command: CommandTexts {
+        description: "alle Befehle anzeigen",
+        usage: &[("", "diese Nachricht")],
+    },
+    add_command: CommandTexts {
+        description: "Aufgabe hinzufügen",
+        usage: &[
+            ("<deine Aufgabe>", "Aufgabe hinzufügen"),
+            ("alle 2 Tage: <deine Aufgabe>", "wiederkehrende Aufgabe hinzufügen"),
+        ],
+    },
+    todos_command: CommandTexts {
+        description: "nummerierte Liste aller Aufgaben",
+        usage: &[("", "nummerierte Liste aller Aufgaben")],
+    },
+    board_command: CommandTexts {
+        description: "angeheftete Aufgabenliste",
+        usage: &[("", "angeheftete Aufgabenliste, die sich selbst aktualisiert")],
+    },
+    my_todos_command: CommandTexts {
+        description: "deine Aufgaben aus allen Gruppen",
+        usage: &[(
+            "",
+            "deine Aufgaben aus allen Gruppen, in einem privaten Chat mit mir",
+        )],
+    },
+    notify_command: CommandTexts {
+        description: "Nachrichten zu deinen Aufgaben",
+        usage: &[(
+            "an",
+            "Nachrichten zu deinen Aufgaben, in einem privaten Chat mit mir",
+        )],
+    },
+    check_command: CommandTexts {
+        description: "Aufgabe (nicht) erledigt",
+        usage: &[("1", "Aufgabe Nummer 1 (nicht) erledigt")],
+    },
+    delete_command: CommandTexts {
+        description: "Aufgabe löschen",
+        usage: &[("2", "Aufgabe Nummer 2 löschen")],
+    },
+    language_command: CommandTexts {
+        description: "Sprache des Chats ändern",
+        usage: &[("en", "switch to english")],
+    },
+    permissions_command: CommandTexts {
+        description: "wer Aufgaben löschen und Einstellungen ändern darf",
+        usage: &[
+            ("", "wer was in diesem Chat darf"),
+            ("loeschen admins", "nur Admins dürfen Aufgaben löschen"),
+        ],
+    },
+    show_command: CommandTexts {
+        description: "Details einer Aufgabe anzeigen",
+        usage: &[("1", "Details zu Aufgabe Nummer 1, z.B. wer sie hinzugefügt hat")],
+    },
+    assign_creator_command: CommandTexts {
+        description: "ob Aufgaben an den gehen, der sie hinzugefügt hat",
+        usage: &[("aus", "Aufgaben nicht dem zuteilen, der sie hinzugefügt hat")],
+    },
+    undo_command: CommandTexts {
+        description: "Löschen, Abhaken oder Zuteilen rückgängig machen",
+        usage: &[("", "das letzte Löschen, Abhaken oder neue Zuteilen einer Aufgabe rückgängig machen")],
+    },
+    confirm_deletes_command: CommandTexts {
+        description: "ob das Löschen jeder Aufgabe bestätigt werden muss",
+        usage: &[("an", "Löschen jeder Aufgabe bestätigen, nicht nur wiederkehrender")],
+    },
+};
+
+/// Replaces each `{name}` in `template` with the value of `name`. Values are inserted as they
+/// are, placeholders in them are not replaced.
+pub fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &rest[1..end])
+                .map(|(_, value)| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                text.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    text
+}
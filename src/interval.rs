@@ -1,132 +1,262 @@
 use crate::{
     error,
-    handler::{chat_member::get_random_chat_member, todo::Todo},
-    util,
+    handler::{
+        board::refresh_board,
+        chat_member::{assignable_members, pick_random_chat_member},
+        language::get_language,
+        notification::notify_chat_member,
+        todo::Todo,
+        undo::{record_undo, UndoAction},
+    },
+    messenger::{send_in_parts, Messenger},
+    render,
+    storage::{AssignedTodo, Storage},
 };
-use frankenstein::{AsyncApi, AsyncTelegramApi, SendMessageParamsBuilder};
-use sqlx::{Pool, Postgres};
-use std::env;
-use tokio::time::{sleep, Duration};
-
-pub async fn interval_actions() -> Result<(), error::LeditError> {
-    let (pool, api) = util::get_pool_and_api().await;
-    let sleep_duration = env::var("INTERVAL_MS")
-        .unwrap_or_else(|_| {
-            tracing::info!("env var `interval_MS` is not set, using default value 10000");
-            "10000".to_string()
-        })
-        .parse::<u64>()
-        .unwrap_or_else(|_| {
-            tracing::warn!(
-                "failed to parse value for env var `interval_MS`, using default value 10000"
-            );
-            10000
-        });
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use frankenstein::SendMessageParamsBuilder;
+use itertools::Itertools;
+use uuid::Uuid;
+
+pub async fn re_schedule_todos(
+    now: DateTime<Tz>,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), error::LeditError> {
+    let today = now.date_naive();
+    let mut re_scheduling = storage.begin_re_scheduling().await?;
+    let todos_to_re_schedule = re_scheduling.get_todos(today).await?;
 
-    tracing::info!("interval sleep duration: {} ms", sleep_duration);
+    if todos_to_re_schedule.is_empty() {
+        // end the transaction first, it may hold the only connection of the pool
+        re_scheduling.commit(&[], today).await?;
+
+        return refresh_boards(&[], today, storage, messenger).await;
+    }
 
-    loop {
-        sleep(Duration::from_millis(sleep_duration)).await;
+    tracing::info!(
+        "amount of todos to re-schedule {:#?}",
+        todos_to_re_schedule.len()
+    );
 
-        match re_schedule_todos(&pool).await {
-            Ok(_) => tracing::info!("re-scheduling todos done"),
-            Err(err) => tracing::error!("re-scheduling todos failed, error: {}", err),
+    // fetch the members and settings of all affected chats at once
+    let chat_ids = todos_to_re_schedule
+        .iter()
+        .map(|v| v.chat_id)
+        .unique()
+        .collect::<Vec<i64>>();
+    let members_by_chat = re_scheduling.get_chat_member_ids(&chat_ids).await?;
+    let assign_creator_by_chat = re_scheduling.get_assign_creator(&chat_ids).await?;
+
+    let mut re_scheduled_todos = Vec::new();
+    for todo in todos_to_re_schedule {
+        let assign_creator = assign_creator_by_chat
+            .get(&todo.chat_id)
+            .copied()
+            .unwrap_or(true);
+        let members = match members_by_chat.get(&todo.chat_id) {
+            Some(members) => assignable_members(members, todo.created_by, assign_creator),
+            None => Vec::new(),
+        };
+
+        match pick_random_chat_member(&members) {
+            Some(assigned_user) => re_scheduled_todos.push((todo, assigned_user)),
+            None => tracing::warn!("no chat member to re-schedule todo {} for", todo.id),
         }
+    }
+    let assignments = re_scheduled_todos
+        .iter()
+        .map(|(todo, assigned_user)| (todo.id, *assigned_user))
+        .collect::<Vec<(Uuid, Uuid)>>();
 
-        match delete_one_time_todos(&pool, &api).await {
-            Ok(_) => tracing::info!("delete one-time todos done"),
-            Err(err) => tracing::error!("delete one-time todos failed, error: {}", err),
+    re_scheduling.commit(&assignments, today).await?;
+
+    for (todo, _) in &re_scheduled_todos {
+        let now = now.with_timezone(&Utc);
+        if let Err(err) = record_undo(UndoAction::Reassign, todo, now, storage).await {
+            tracing::error!(
+                "failed to record re-assigning todo {}, err: {}",
+                todo.id,
+                err
+            );
         }
     }
+
+    for (todo, assigned_user) in &re_scheduled_todos {
+        notify_chat_member(
+            *assigned_user,
+            todo.chat_id,
+            &todo.description,
+            |v| v.re_scheduled_todo_notice,
+            storage,
+            messenger,
+        )
+        .await;
+    }
+
+    let re_scheduled_chat_ids = re_scheduled_todos
+        .iter()
+        .map(|(todo, _)| todo.chat_id)
+        .unique()
+        .collect::<Vec<i64>>();
+    refresh_boards(&re_scheduled_chat_ids, today, storage, messenger).await
 }
 
-async fn re_schedule_todos(pool: &Pool<Postgres>) -> Result<(), error::LeditError> {
-    tracing::info!("re-schedule todos");
-
-    let todos_to_re_schedule = sqlx::query_as!(
-        Todo,
-        r#"
-            select *
-            from todos
-            where 
-                interval_days is not null
-                and done_by is not null
-                and scheduled_for < now() - interval '1 days' * interval_days
-        "#
-    )
-    .fetch_all(pool)
-    .await?;
-
-    if !todos_to_re_schedule.is_empty() {
-        tracing::info!(
-            "amount of todos to re-schedule {:#?}",
-            todos_to_re_schedule.len()
-        );
+pub async fn delete_one_time_todos(
+    retention_days: i64,
+    today: NaiveDate,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), error::LeditError> {
+    let todos = storage
+        .delete_done_one_time_todos(retention_days, today)
+        .await?;
+
+    // a chat that blocked the bot must not keep the others from their notices
+    for todo in &todos {
+        if let Err(err) = send_deletion_notice(todo, storage, messenger).await {
+            tracing::error!(
+                "failed to send deletion notice to chat {}, err: {}",
+                todo.chat_id,
+                err
+            );
+        }
     }
 
-    for todo in todos_to_re_schedule {
-        let assigned_user = get_random_chat_member(todo.chat_id, pool).await?;
-        sqlx::query!(
-            r#"
-                update todos
-                set
-                    done_by = null,
-                    scheduled_for = now(),
-                    assigned_user = $2
-                where 
-                    id = $1
-            "#,
-            todo.id,
-            assigned_user
+    for chat_id in todos.iter().map(|v| v.chat_id).unique() {
+        if let Err(err) = refresh_board(chat_id, today, storage, messenger).await {
+            tracing::error!("failed to refresh board of chat {}, err: {}", chat_id, err);
+        }
+    }
+
+    Ok(())
+}
+
+async fn send_deletion_notice(
+    todo: &Todo,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), error::LeditError> {
+    let texts = get_language(todo.chat_id, storage).await?.texts();
+    messenger
+        .send_message(
+            &SendMessageParamsBuilder::default()
+                .chat_id(todo.chat_id)
+                .text(render::deleting_old_todo(todo, texts))
+                .parse_mode("HTML")
+                .build()?,
         )
-        .execute(pool)
         .await?;
+
+    Ok(())
+}
+
+/// Refreshes the boards of the chats with re-scheduled todos and, once a day, all others, as
+/// they still show the todos of yesterday.
+async fn refresh_boards(
+    re_scheduled_chat_ids: &[i64],
+    today: NaiveDate,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), error::LeditError> {
+    let stale_chat_ids = storage.get_stale_board_chat_ids(today).await?;
+
+    for chat_id in re_scheduled_chat_ids.iter().chain(&stale_chat_ids).unique() {
+        if let Err(err) = refresh_board(*chat_id, today, storage, messenger).await {
+            tracing::error!("failed to refresh board of chat {}, err: {}", chat_id, err);
+        }
+        // a failing board is tried again on the next day only
+        storage.set_board_refreshed_on(*chat_id, today).await?;
     }
 
     Ok(())
 }
 
-async fn delete_one_time_todos(
-    pool: &Pool<Postgres>,
-    api: &AsyncApi,
+pub async fn send_reminders(
+    today: NaiveDate,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
 ) -> Result<(), error::LeditError> {
-    tracing::info!("delete one-time todos");
-
-    let todos = sqlx::query_as!(
-        Todo,
-        r#"
-            select * from 
-                todos 
-            where 
-                done_by is not null 
-                and interval_days is null 
-                and scheduled_for < now() - interval '1 day'
-        "#
-    )
-    .fetch_all(pool)
-    .await?;
+    let overdue_todos_by_chat = storage
+        .get_overdue_todos(today)
+        .await?
+        .into_iter()
+        .into_group_map_by(|v| v.chat_id);
 
+    for (chat_id, todos) in overdue_todos_by_chat {
+        if let Err(err) = send_reminder(chat_id, &todos, storage, messenger).await {
+            tracing::error!("failed to send reminder to chat {}, err: {}", chat_id, err);
+        }
+    }
+
+    Ok(())
+}
+
+async fn send_reminder(
+    chat_id: i64,
+    todos: &[AssignedTodo],
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), error::LeditError> {
     for todo in todos {
-        sqlx::query!(
-            r#"
-                delete from 
-                    todos 
-                where 
-                    id = $1
-            "#,
-            todo.id
+        notify_chat_member(
+            todo.assigned_user,
+            chat_id,
+            &todo.description,
+            |v| v.overdue_todo_notice,
+            storage,
+            messenger,
         )
-        .execute(pool)
-        .await?;
+        .await;
+    }
 
-        api.send_message(
-            &SendMessageParamsBuilder::default()
-                .chat_id(todo.chat_id)
-                .text(&format!("🗑 Deleting old & done todo: {}", todo.description))
-                .build()?,
-        )
-        .await?;
+    let texts = get_language(chat_id, storage).await?.texts();
+    let text = render::reminder(todos, texts);
+
+    send_in_parts(
+        messenger,
+        &SendMessageParamsBuilder::default()
+            .chat_id(chat_id)
+            .text(text)
+            .parse_mode("HTML")
+            .build()?,
+    )
+    .await
+}
+
+pub async fn send_digests(
+    today: NaiveDate,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), error::LeditError> {
+    let chat_ids = storage.get_chat_ids().await?;
+
+    for chat_id in chat_ids {
+        if let Err(err) = send_digest(chat_id, today, storage, messenger).await {
+            tracing::error!("failed to send digest to chat {}, err: {}", chat_id, err);
+        }
     }
 
     Ok(())
 }
+
+async fn send_digest(
+    chat_id: i64,
+    today: NaiveDate,
+    storage: &dyn Storage,
+    messenger: &dyn Messenger,
+) -> Result<(), error::LeditError> {
+    let todos = storage.get_actionable_todos(chat_id, today).await?;
+    let texts = get_language(chat_id, storage).await?.texts();
+    let text = render::todos_by_member(&todos, today, texts);
+
+    send_in_parts(
+        messenger,
+        &SendMessageParamsBuilder::default()
+            .chat_id(chat_id)
+            .text(text)
+            .parse_mode("HTML")
+            .build()?,
+    )
+    .await
+}
@@ -0,0 +1,57 @@
+//! ledit is a telegram bot that assigns recurring todos to the members of a group chat.
+//!
+//! The `ledit` binary only wires things together. Everything else lives here, so it can be
+//! reused by other tooling and tested from `tests/`. The modules are private, the items
+//! re-exported here are the public API.
+
+mod action;
+mod bot;
+mod clock;
+mod command;
+mod config;
+mod context;
+mod error;
+mod handler;
+mod i18n;
+mod interval;
+mod messenger;
+mod render;
+mod scheduler;
+mod storage;
+mod util;
+
+pub use action::Action;
+pub use bot::{handle_callback_query, handle_message, listen_for_updates};
+pub use clock::{simulate, Clock, ManualClock, SystemClock};
+pub use command::{register_commands, Command};
+pub use config::Config;
+pub use context::Context;
+pub use error::LeditError;
+pub use handler::{
+    chat_member::{assignable_members, pick_random_chat_member, ChatMember},
+    todo::Todo,
+    undo::UndoEntry,
+};
+pub use i18n::Language;
+pub use interval::{delete_one_time_todos, re_schedule_todos, send_digests, send_reminders};
+pub use messenger::Messenger;
+pub use scheduler::{run_job_if_due, run_scheduler, Job, Schedule};
+pub use storage::{connect, AssignedTodo, PgStorage, SqliteStorage, Storage, UserTodo};
+pub use util::{get_storage_and_api, shutdown_signal};
+
+/// Fakes and internals for the tests in `tests/`, enabled by the `testing` feature. Not part of
+/// the public API.
+#[cfg(feature = "testing")]
+pub mod testing {
+    pub use crate::{
+        command::COMMANDS,
+        handler::chat_member::display_name,
+        i18n::{fill, DE, EN},
+        messenger::RecordingMessenger,
+        storage::{JobLock, ReScheduling},
+    };
+
+    pub mod render {
+        pub use crate::render::*;
+    }
+}
@@ -1,17 +1,11 @@
-#[macro_use]
-extern crate log;
+use chrono::Utc;
 use dotenv::dotenv;
-use frankenstein::{AsyncTelegramApi as TelegramApi, GetUpdatesParamsBuilder};
+use ledit::{
+    get_storage_and_api, listen_for_updates, register_commands, run_scheduler, shutdown_signal,
+    simulate, Clock, Config, Context, ManualClock, SystemClock,
+};
 use std::error::Error;
-
-mod action;
-mod error;
-mod handler;
-mod interval;
-mod util;
-use action::Action;
-use handler::chat_member::register_chat_member;
-use interval::interval_actions;
+use tokio::sync::watch;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -22,72 +16,67 @@ async fn main() -> Result<(), Box<dyn Error>> {
     }
     tracing_subscriber::fmt::init();
 
-    // start bot
-    tracing::info!("starting bot...");
-    let (_, _) = tokio::join!(listen_for_updates(), interval_actions());
-
-    Ok(())
-}
-
-async fn listen_for_updates() -> Result<(), error::LeditError> {
-    let (pool, api) = util::get_pool_and_api().await;
-
-    sqlx::migrate!("./migrations").run(&pool).await?;
-
-    let mut update_params_builder = GetUpdatesParamsBuilder::default();
-    update_params_builder.allowed_updates(vec!["message".to_string()]);
+    // load config
+    let config = Config::load().map_err(|err| {
+        tracing::error!("failed to load config, err: {}", err);
+        err
+    })?;
 
-    let mut update_params = update_params_builder.build().unwrap();
+    let (storage, api) = get_storage_and_api(&config).await?;
+    storage.migrate().await?;
 
-    loop {
-        let result = api.get_updates(&update_params).await;
+    // a missing command menu must not keep the bot from starting
+    if let Err(err) = register_commands(&api).await {
+        tracing::error!("failed to register commands, err: {}", err);
+    }
 
-        tracing::debug!("received telegram api update");
+    // stop gracefully on SIGTERM/SIGINT
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let shutdown = async move {
+        shutdown_signal().await;
+        tracing::info!("received shutdown signal, finishing in-flight work...");
+        shutdown_tx.send(true).ok();
+    };
 
-        match result {
-            Ok(response) => {
-                for update in response.result {
-                    let response = if let Some(message) = update.message {
-                        let action = Action::from_message(&message);
-                        tracing::info!("action: {}", action);
+    // in simulation mode the clock moves forward by a fixed step on every tick
+    let simulated_clock = config
+        .simulation_step_minutes
+        .map(|_| ManualClock::new(Utc::now()));
+    let clock: &dyn Clock = match &simulated_clock {
+        Some(clock) => clock,
+        None => &SystemClock,
+    };
+    let simulation = async {
+        if let (Some(clock), Some(step)) = (&simulated_clock, config.simulation_step_minutes) {
+            tracing::warn!("simulation mode, time advances {} minutes per tick", step);
+            simulate(clock, step, config.interval_ms, shutdown_rx.clone()).await;
+        }
+    };
 
-                        if let Err(err) = register_chat_member(&message, &pool).await {
-                            tracing::error!("failed to register chat member, err: {}", err);
-                        }
+    let ctx = Context {
+        storage: storage.as_ref(),
+        config: &config,
+        messenger: &api,
+        clock,
+    };
 
-                        let response = action.execute(&pool).await;
-                        match response {
-                            Ok(_) => Some(response),
-                            Err(err) => {
-                                tracing::error!("failed to respond to action, err: {}", err);
-                                None
-                            }
-                        }
-                    } else {
-                        None
-                    };
+    // start bot
+    tracing::info!("starting bot...");
+    let (_, _, updates_result, scheduler_result) = tokio::join!(
+        shutdown,
+        simulation,
+        listen_for_updates(ctx, shutdown_rx.clone()),
+        run_scheduler(ctx, shutdown_rx.clone()),
+    );
+    if let Err(err) = updates_result {
+        tracing::error!("listening for updates failed, err: {}", err);
+    }
+    if let Err(err) = scheduler_result {
+        tracing::error!("scheduler failed, err: {}", err);
+    }
 
-                    match response {
-                        Some(Err(err)) => {
-                            error!("{:#?}", err);
-                        }
-                        Some(Ok(Some(response))) => {
-                            if let Err(err) = api.send_message(&response).await {
-                                error!("failed to send message: {:?}", err);
-                            }
-                        }
-                        _ => {}
-                    }
+    storage.close().await;
+    tracing::info!("bot stopped");
 
-                    update_params = update_params_builder
-                        .offset(update.update_id + 1)
-                        .build()
-                        .unwrap();
-                }
-            }
-            Err(error) => {
-                tracing::error!("failed to process telegram api update, err: {:?}", error);
-            }
-        }
-    }
+    Ok(())
 }
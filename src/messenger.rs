@@ -0,0 +1,355 @@
+use crate::{error::LeditError, render::split_message};
+use async_trait::async_trait;
+use frankenstein::{
+    objects::ChatMember, AnswerCallbackQueryParams, AsyncApi, AsyncTelegramApi,
+    EditMessageTextParams, GetChatAdministratorsParams, GetUpdatesParams, Message,
+    PinChatMessageParams, SendMessageParams, SetMyCommandsParams, Update, User,
+};
+
+/// The parts of the Telegram bot API used by ledit.
+#[async_trait]
+pub trait Messenger: Send + Sync {
+    async fn get_updates(&self, params: &GetUpdatesParams) -> Result<Vec<Update>, LeditError>;
+
+    async fn send_message(&self, params: &SendMessageParams) -> Result<Message, LeditError>;
+
+    async fn edit_message_text(&self, params: &EditMessageTextParams) -> Result<(), LeditError>;
+
+    async fn pin_chat_message(&self, params: &PinChatMessageParams) -> Result<(), LeditError>;
+
+    async fn answer_callback_query(
+        &self,
+        params: &AnswerCallbackQueryParams,
+    ) -> Result<(), LeditError>;
+
+    async fn set_my_commands(&self, params: &SetMyCommandsParams) -> Result<(), LeditError>;
+
+    /// Returns the users who own or administrate the chat.
+    async fn get_chat_administrators(
+        &self,
+        params: &GetChatAdministratorsParams,
+    ) -> Result<Vec<User>, LeditError>;
+}
+
+/// Maximum length of a message text accepted by telegram.
+pub const MAX_MESSAGE_LENGTH: usize = 4096;
+
+/// Sends `params`, split into several messages if the text exceeds telegram's limit.
+pub async fn send_in_parts(
+    messenger: &dyn Messenger,
+    params: &SendMessageParams,
+) -> Result<(), LeditError> {
+    for text in split_message(&params.text, MAX_MESSAGE_LENGTH) {
+        let mut part = params.clone();
+        part.text = text;
+
+        messenger.send_message(&part).await?;
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl Messenger for AsyncApi {
+    async fn get_updates(&self, params: &GetUpdatesParams) -> Result<Vec<Update>, LeditError> {
+        Ok(AsyncTelegramApi::get_updates(self, params).await?.result)
+    }
+
+    async fn send_message(&self, params: &SendMessageParams) -> Result<Message, LeditError> {
+        Ok(AsyncTelegramApi::send_message(self, params).await?.result)
+    }
+
+    async fn edit_message_text(&self, params: &EditMessageTextParams) -> Result<(), LeditError> {
+        AsyncTelegramApi::edit_message_text(self, params).await?;
+
+        Ok(())
+    }
+
+    async fn pin_chat_message(&self, params: &PinChatMessageParams) -> Result<(), LeditError> {
+        AsyncTelegramApi::pin_chat_message(self, params).await?;
+
+        Ok(())
+    }
+
+    async fn answer_callback_query(
+        &self,
+        params: &AnswerCallbackQueryParams,
+    ) -> Result<(), LeditError> {
+        AsyncTelegramApi::answer_callback_query(self, params).await?;
+
+        Ok(())
+    }
+
+    async fn set_my_commands(&self, params: &SetMyCommandsParams) -> Result<(), LeditError> {
+        AsyncTelegramApi::set_my_commands(self, params).await?;
+
+        Ok(())
+    }
+
+    async fn get_chat_administrators(
+        &self,
+        params: &GetChatAdministratorsParams,
+    ) -> Result<Vec<User>, LeditError> {
+        let admins = AsyncTelegramApi::get_chat_administrators(self, params)
+            .await?
+            .result
+            .into_iter()
+            .filter_map(|v| match v {
+                ChatMember::Owner(v) => Some(v.user),
+                ChatMember::Administrator(v) => Some(v.user),
+                _ => None,
+            })
+            .collect();
+
+        Ok(admins)
+    }
+}
+
+#[cfg(feature = "testing")]
+pub use fake::RecordingMessenger;
+
+#[cfg(feature = "testing")]
+mod fake {
+    use super::{Messenger, MAX_MESSAGE_LENGTH};
+    use crate::error::LeditError;
+    use async_trait::async_trait;
+    use frankenstein::{
+        objects::{ChatBuilder, ChatType, MessageBuilder, UserBuilder},
+        AnswerCallbackQueryParams, ChatId, EditMessageTextParams, GetChatAdministratorsParams,
+        GetUpdatesParams, Message, PinChatMessageParams, SendMessageParams, SetMyCommandsParams,
+        Update, User,
+    };
+    use std::{
+        collections::{HashMap, HashSet, VecDeque},
+        sync::{
+            atomic::{AtomicI32, Ordering},
+            Mutex,
+        },
+    };
+
+    /// In-memory messenger that hands out queued updates and records sent messages.
+    #[derive(Debug, Default)]
+    pub struct RecordingMessenger {
+        updates: Mutex<VecDeque<Update>>,
+        sent: Mutex<Vec<SendMessageParams>>,
+        edits: Mutex<Vec<EditMessageTextParams>>,
+        pins: Mutex<Vec<PinChatMessageParams>>,
+        answers: Mutex<Vec<AnswerCallbackQueryParams>>,
+        commands: Mutex<Vec<SetMyCommandsParams>>,
+        blocked_by: Mutex<HashSet<i64>>,
+        admins: Mutex<HashMap<i64, Vec<u64>>>,
+        // current text of every sent message by chat and message id
+        texts: Mutex<HashMap<(i64, i32), String>>,
+        last_message_id: AtomicI32,
+    }
+
+    impl RecordingMessenger {
+        pub fn push_update(&self, update: Update) {
+            self.updates.lock().unwrap().push_back(update);
+        }
+
+        pub fn sent_messages(&self) -> Vec<SendMessageParams> {
+            self.sent.lock().unwrap().clone()
+        }
+
+        /// Returns the texts of all sent messages and clears the record.
+        pub fn take_texts(&self) -> Vec<String> {
+            self.sent
+                .lock()
+                .unwrap()
+                .drain(..)
+                .map(|v| v.text)
+                .collect()
+        }
+
+        /// Returns all sent messages and clears the record.
+        pub fn take_messages(&self) -> Vec<SendMessageParams> {
+            self.sent.lock().unwrap().drain(..).collect()
+        }
+
+        /// Makes sending to the private chat of the telegram user fail like it does after they
+        /// blocked the bot.
+        pub fn block_by(&self, telegram_user_id: i64) {
+            self.blocked_by.lock().unwrap().insert(telegram_user_id);
+        }
+
+        /// Makes the telegram users the admins of the chat.
+        pub fn set_admins(&self, chat_id: i64, telegram_user_ids: &[u64]) {
+            self.admins
+                .lock()
+                .unwrap()
+                .insert(chat_id, telegram_user_ids.to_vec());
+        }
+
+        /// Returns all message edits and clears the record.
+        pub fn take_edits(&self) -> Vec<EditMessageTextParams> {
+            self.edits.lock().unwrap().drain(..).collect()
+        }
+
+        pub fn pinned_messages(&self) -> Vec<PinChatMessageParams> {
+            self.pins.lock().unwrap().clone()
+        }
+
+        /// Returns all answers to callback queries and clears the record.
+        pub fn take_answers(&self) -> Vec<AnswerCallbackQueryParams> {
+            self.answers.lock().unwrap().drain(..).collect()
+        }
+
+        /// Returns the registered command menus, the latest one per language last.
+        pub fn command_menus(&self) -> Vec<SetMyCommandsParams> {
+            self.commands.lock().unwrap().clone()
+        }
+
+        /// Returns the id of the message sent last.
+        pub fn last_message_id(&self) -> i32 {
+            self.last_message_id.load(Ordering::SeqCst)
+        }
+
+        /// Returns the current text of a sent message, including edits.
+        pub fn message_text(&self, chat_id: i64, message_id: i32) -> Option<String> {
+            self.texts
+                .lock()
+                .unwrap()
+                .get(&(chat_id, message_id))
+                .cloned()
+        }
+
+        /// Forgets a sent message, as if it was deleted in the chat.
+        pub fn delete_message(&self, chat_id: i64, message_id: i32) {
+            self.texts.lock().unwrap().remove(&(chat_id, message_id));
+        }
+    }
+
+    fn chat_id_to_i64(chat_id: &ChatId) -> i64 {
+        match chat_id {
+            ChatId::Integer(id) => *id,
+            ChatId::String(_) => 0,
+        }
+    }
+
+    fn bad_request(description: &str) -> LeditError {
+        LeditError::TelegramRejected {
+            code: 400,
+            description: format!("Bad Request: {}", description),
+        }
+    }
+
+    #[async_trait]
+    impl Messenger for RecordingMessenger {
+        async fn get_updates(&self, _: &GetUpdatesParams) -> Result<Vec<Update>, LeditError> {
+            // yield like a real request would, so polling loops don't starve other tasks
+            tokio::task::yield_now().await;
+            Ok(self.updates.lock().unwrap().drain(..).collect())
+        }
+
+        async fn send_message(&self, params: &SendMessageParams) -> Result<Message, LeditError> {
+            let chat_id = chat_id_to_i64(&params.chat_id);
+            if self.blocked_by.lock().unwrap().contains(&chat_id) {
+                return Err(LeditError::TelegramRejected {
+                    code: 403,
+                    description: "Forbidden: bot was blocked by the user".to_string(),
+                });
+            }
+
+            if params.text.encode_utf16().count() > MAX_MESSAGE_LENGTH {
+                return Err(bad_request("message is too long"));
+            }
+
+            self.sent.lock().unwrap().push(params.clone());
+
+            let message_id = self.last_message_id.fetch_add(1, Ordering::SeqCst) + 1;
+            self.texts
+                .lock()
+                .unwrap()
+                .insert((chat_id, message_id), params.text.clone());
+
+            Ok(MessageBuilder::default()
+                .message_id(message_id)
+                .date(0u64)
+                .chat(
+                    ChatBuilder::default()
+                        .id(chat_id)
+                        .type_field(ChatType::Group)
+                        .build()
+                        .unwrap(),
+                )
+                .text(params.text.clone())
+                .build()
+                .unwrap())
+        }
+
+        async fn edit_message_text(
+            &self,
+            params: &EditMessageTextParams,
+        ) -> Result<(), LeditError> {
+            let chat_id = params
+                .chat_id
+                .as_ref()
+                .map(chat_id_to_i64)
+                .unwrap_or_default();
+            let key = (chat_id, params.message_id.unwrap_or_default());
+            if params.text.encode_utf16().count() > MAX_MESSAGE_LENGTH {
+                return Err(bad_request("message is too long"));
+            }
+
+            let mut texts = self.texts.lock().unwrap();
+            match texts.get(&key) {
+                None => return Err(bad_request("message to edit not found")),
+                Some(text) if *text == params.text => {
+                    return Err(bad_request("message is not modified"))
+                }
+                Some(_) => {}
+            }
+            texts.insert(key, params.text.clone());
+            self.edits.lock().unwrap().push(params.clone());
+
+            Ok(())
+        }
+
+        async fn pin_chat_message(&self, params: &PinChatMessageParams) -> Result<(), LeditError> {
+            self.pins.lock().unwrap().push(params.clone());
+
+            Ok(())
+        }
+
+        async fn answer_callback_query(
+            &self,
+            params: &AnswerCallbackQueryParams,
+        ) -> Result<(), LeditError> {
+            self.answers.lock().unwrap().push(params.clone());
+
+            Ok(())
+        }
+
+        async fn set_my_commands(&self, params: &SetMyCommandsParams) -> Result<(), LeditError> {
+            self.commands.lock().unwrap().push(params.clone());
+
+            Ok(())
+        }
+
+        async fn get_chat_administrators(
+            &self,
+            params: &GetChatAdministratorsParams,
+        ) -> Result<Vec<User>, LeditError> {
+            let admins = self
+                .admins
+                .lock()
+                .unwrap()
+                .get(&chat_id_to_i64(&params.chat_id))
+                .cloned()
+                .unwrap_or_default();
+
+            Ok(admins
+                .into_iter()
+                .map(|id| {
+                    UserBuilder::default()
+                        .id(id)
+                        .is_bot(false)
+                        .first_name("admin")
+                        .build()
+                        .unwrap()
+                })
+                .collect())
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! Message content for todos and chat members.
+//!
+//! Everything here is pure and produces HTML, so it has to be sent with the HTML parse mode.
+//! Texts come from the message catalog of the chat's language.
+
+use crate::{
+    command::Command,
+    handler::{
+        chat_member::ChatMember,
+        permission::{Operation, Permission},
+        todo::Todo,
+    },
+    i18n::{fill, Language, Texts},
+    storage::{AssignedTodo, UserTodo},
+};
+use chrono::{Duration, NaiveDate};
+use itertools::Itertools;
+use uuid::Uuid;
+
+pub fn added_todo(todo: &Todo, texts: &Texts) -> String {
+    fill(
+        texts.added_todo,
+        &[("todo", &escape_html(&todo.description))],
+    )
+}
+
+pub fn checked_todo(todo: &Todo) -> String {
+    let checkbox = if todo.done_by.is_some() {
+        "✅"
+    } else {
+        "☑️"
+    };
+
+    format!("{} {}", checkbox, escape_html(&todo.description))
+}
+
+/// Everything about `todo`, the names of the chat `members` it refers to and the date it was
+/// `created_on` in the chat's timezone.
+pub fn todo_details(
+    todo: &Todo,
+    members: &[ChatMember],
+    created_on: Option<NaiveDate>,
+    texts: &Texts,
+) -> String {
+    let name = |id: Uuid| {
+        members
+            .iter()
+            .find(|v| v.id == id)
+            .map(|v| escape_html(&v.display_name))
+            .unwrap_or_else(|| texts.former_member.to_string())
+    };
+
+    let mut lines = vec![format!("<b>{}</b>", escape_html(&todo.description))];
+    lines.push(match todo.interval_days {
+        Some(interval_days) => interval(interval_days, texts),
+        None => texts.one_time_todo.to_string(),
+    });
+    lines.push(fill(
+        texts.todo_assigned_to,
+        &[("member", &name(todo.assigned_user))],
+    ));
+    lines.push(fill(
+        texts.todo_scheduled_for,
+        &[("date", &format_date(todo.scheduled_for, texts))],
+    ));
+    lines.push(match todo.done_by {
+        Some(done_by) => fill(texts.todo_done_by, &[("member", &name(done_by))]),
+        None => texts.todo_open.to_string(),
+    });
+    lines.push(match created_on {
+        Some(created_on) => {
+            let creator = match todo.created_by {
+                Some(created_by) => name(created_by),
+                None => texts.former_member.to_string(),
+            };
+            fill(
+                texts.todo_created,
+                &[
+                    ("member", &creator),
+                    ("date", &format_date(created_on, texts)),
+                ],
+            )
+        }
+        None => texts.todo_created_unknown.to_string(),
+    });
+
+    lines.join("\n")
+}
+
+/// Confirms the deletion of `todo` followed by the list of the `remaining` todos.
+pub fn deleted_todo(todo: &Todo, remaining: &[Todo], today: NaiveDate, texts: &Texts) -> String {
+    format!(
+        "{}\n\n{}",
+        fill(
+            texts.deleted_todo,
+            &[("todo", &escape_html(&todo.description))]
+        ),
+        todo_list(remaining, today, texts)
+    )
+}
+
+/// Asks whether to delete `todo`, with its interval so a recurring todo is recognized.
+pub fn delete_question(todo: &Todo, texts: &Texts) -> String {
+    let mut name = format!("<b>{}</b>", escape_html(&todo.description));
+    if let Some(interval_days) = todo.interval_days {
+        name = format!("{} ({})", name, interval(interval_days, texts));
+    }
+
+    fill(texts.delete_question, &[("todo", &name)])
+}
+
+/// The content of the pinned board of a chat, cut to fit into a single message.
+pub fn board(
+    todos: &[Todo],
+    actionable_todos: &[AssignedTodo],
+    today: NaiveDate,
+    limit: usize,
+    texts: &Texts,
+) -> String {
+    let text = format!(
+        "{}\n\n{}",
+        texts.board_title,
+        todo_overview(todos, actionable_todos, today, texts)
+    );
+
+    truncate_message(&text, limit)
+}
+
+/// The list of all todos followed by the todos of today by member.
+pub fn todo_overview(
+    todos: &[Todo],
+    actionable_todos: &[AssignedTodo],
+    today: NaiveDate,
+    texts: &Texts,
+) -> String {
+    format!(
+        "{}\n\n\n{}",
+        todo_list(todos, today, texts),
+        todos_by_member(actionable_todos, today, texts)
+    )
+}
+
+/// Numbered list of all todos of a chat, `todos` have to be sorted like the storage does.
+pub fn todo_list(todos: &[Todo], today: NaiveDate, texts: &Texts) -> String {
+    if todos.is_empty() {
+        return texts.todo_list_empty.to_string();
+    }
+
+    let mut text = format!("{}\n", texts.todo_list_title);
+    for (n, todo) in (1..).zip(todos.iter()) {
+        let overdue = todo.done_by.is_none() && todo.scheduled_for < today;
+        text.push_str(&format!(
+            "\n{} {}. {} {} ",
+            list_checkbox(todo, today),
+            n,
+            format_description(&todo.description, todo.done_by.is_some(), overdue),
+            list_suffix(todo, today, texts)
+        ));
+    }
+
+    text
+}
+
+/// Todos of today grouped by the member they are assigned to, mentioning each member.
+pub fn todos_by_member(todos: &[AssignedTodo], today: NaiveDate, texts: &Texts) -> String {
+    if todos.is_empty() {
+        return texts.todos_of_today_empty.to_string();
+    }
+
+    let mut todos_by_member = todos
+        .iter()
+        // group by member, display names are not unique
+        .into_group_map_by(|v| (v.display_name.clone(), v.telegram_user_id))
+        .into_iter()
+        .collect::<Vec<((String, i64), _)>>();
+
+    todos_by_member.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    todos_by_member
+        .into_iter()
+        .map(|((display_name, telegram_user_id), todos)| {
+            let mut r = format!(
+                "{}\n",
+                fill(
+                    texts.todos_of_member,
+                    &[("member", &mention(telegram_user_id, &display_name))]
+                )
+            );
+            for todo in todos {
+                let overdue = todo.scheduled_for < today && todo.done_by.is_none();
+                let delay = if overdue { "⏳" } else { "" };
+
+                r.push_str(&format!(
+                    "\n{}{} {}",
+                    member_checkbox(todo),
+                    delay,
+                    format_description(&todo.description, todo.done_by.is_some(), overdue)
+                ));
+            }
+            r
+        })
+        .join("\n\n")
+}
+
+/// Reminder of overdue `todos`, mentioning the members they are assigned to.
+pub fn reminder(todos: &[AssignedTodo], texts: &Texts) -> String {
+    let mut text = format!("{}\n", texts.reminder_title);
+    for todo in todos {
+        text.push_str(&format!(
+            "\n☑️⏳ {} ({})",
+            format_description(&todo.description, false, true),
+            mention(todo.telegram_user_id, &todo.display_name)
+        ));
+    }
+
+    text
+}
+
+pub fn deleting_old_todo(todo: &Todo, texts: &Texts) -> String {
+    fill(
+        texts.deleting_old_todo,
+        &[("todo", &escape_html(&todo.description))],
+    )
+}
+
+/// The todos of a user in all their chats, grouped by chat title.
+pub fn my_todos(todos: &[UserTodo], today: NaiveDate, texts: &Texts) -> String {
+    if todos.is_empty() {
+        return texts.my_todos_empty.to_string();
+    }
+
+    let mut text = texts.my_todos_title.to_string();
+    for (chat_id, todos) in &todos.iter().group_by(|v| v.chat_id) {
+        let todos = todos.collect::<Vec<&UserTodo>>();
+        text.push_str(&format!(
+            "\n\n<b>{}</b>",
+            escape_html(&chat_title(chat_id, todos[0].chat_title.as_deref(), texts))
+        ));
+
+        for todo in todos {
+            let overdue = todo.scheduled_for < today && todo.done_by.is_none();
+            let delay = if overdue { "⏳" } else { "" };
+
+            text.push_str(&format!(
+                "\n{}{} {}",
+                user_checkbox(todo),
+                delay,
+                format_description(&todo.description, todo.done_by.is_some(), overdue)
+            ));
+        }
+    }
+
+    text
+}
+
+/// Label of the button checking `todo`, buttons show plain text.
+pub fn my_todo_button(todo: &UserTodo, texts: &Texts) -> String {
+    format!(
+        "{} {} ({})",
+        user_checkbox(todo),
+        todo.description,
+        chat_title(todo.chat_id, todo.chat_title.as_deref(), texts)
+    )
+}
+
+/// Answer to checking `todo` with a button, answers show plain text.
+pub fn checked_todo_notice(todo: &Todo, texts: &Texts) -> String {
+    let template = if todo.done_by.is_some() {
+        texts.checked_todo_notice
+    } else {
+        texts.unchecked_todo_notice
+    };
+
+    fill(template, &[("todo", &todo.description)])
+}
+
+/// Direct message about the todo `description` of the chat `chat_title`, `template` is one
+/// of the notices of the catalog.
+pub fn todo_notice(template: &str, description: &str, chat_title: &str) -> String {
+    fill(
+        template,
+        &[
+            ("chat", &escape_html(chat_title)),
+            ("todo", &escape_html(description)),
+        ],
+    )
+}
+
+/// One line per usage of the listed `commands`, named in `language`.
+pub fn help(commands: &[Command], language: Language) -> String {
+    commands
+        .iter()
+        .filter_map(|command| {
+            command
+                .texts
+                .map(|texts| (command, texts(language.texts())))
+        })
+        .flat_map(|(command, texts)| {
+            let name = command.name_in(language);
+            texts.usage.iter().map(move |(args, description)| {
+                if args.is_empty() {
+                    format!("/{} - {}", name, escape_html(description))
+                } else {
+                    format!(
+                        "/{} {} - {}",
+                        name,
+                        escape_html(args),
+                        escape_html(description)
+                    )
+                }
+            })
+        })
+        .join("\n")
+}
+
+pub fn permissions(permissions: &[(Operation, Permission)], texts: &Texts) -> String {
+    let lines = permissions
+        .iter()
+        .map(|(operation, permission)| {
+            format!(
+                "{}: <b>{}</b>",
+                operation.name(texts),
+                permission.name(texts)
+            )
+        })
+        .join("\n");
+
+    format!(
+        "{}\n\n{}\n\n{}",
+        texts.permissions_title, lines, texts.permissions_usage
+    )
+}
+
+/// Splits `text` into parts of at most `limit` UTF-16 code units at line breaks, so the
+/// formatting of a line stays intact. Only a line that exceeds the limit on its own is cut.
+///
+/// Telegram measures messages in UTF-16 code units, in which most emojis take two.
+pub fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+
+    for line in text.split('\n') {
+        let line_len = line.encode_utf16().count();
+
+        if let Some((part, part_len)) = current.as_mut() {
+            if *part_len + 1 + line_len <= limit {
+                part.push('\n');
+                part.push_str(line);
+                *part_len += 1 + line_len;
+                continue;
+            }
+            parts.extend(
+                current
+                    .take()
+                    .map(|(v, _)| v.trim_end_matches('\n').to_string()),
+            );
+        }
+
+        // drop the blank lines separating the parts
+        if line.is_empty() && !parts.is_empty() {
+            continue;
+        }
+
+        // cut between characters, a character is never split
+        let mut chunk = String::new();
+        let mut chunk_len = 0;
+        for c in line.chars() {
+            if !chunk.is_empty() && chunk_len + c.len_utf16() > limit {
+                parts.push(std::mem::take(&mut chunk));
+                chunk_len = 0;
+            }
+            chunk.push(c);
+            chunk_len += c.len_utf16();
+        }
+        current = Some((chunk, chunk_len));
+    }
+    parts.extend(current.map(|(v, _)| v));
+
+    parts
+}
+
+/// Cuts `text` to its first part of at most `limit` UTF-16 code units, marking the cut with "…".
+pub fn truncate_message(text: &str, limit: usize) -> String {
+    let mut parts = split_message(text, limit.saturating_sub(2)).into_iter();
+    let first = parts.next().unwrap_or_default();
+    match parts.next() {
+        Some(_) => format!("{}\n…", first),
+        None => first,
+    }
+}
+
+/// ✅ done today, 🗓 done and waiting to be re-scheduled, ☑️ open.
+fn list_checkbox(todo: &Todo, today: NaiveDate) -> &'static str {
+    match todo.done_by {
+        Some(_) if todo.scheduled_for == today => "✅",
+        Some(_) => "🗓",
+        None => "☑️",
+    }
+}
+
+/// ✅ done by the assigned member, ✅↪️ done by someone else, ☑️ open.
+fn member_checkbox(todo: &AssignedTodo) -> &'static str {
+    match todo.done_by {
+        Some(done_by) if done_by == todo.assigned_user => "✅",
+        Some(_) => "✅↪️",
+        None => "☑️",
+    }
+}
+
+fn user_checkbox(todo: &UserTodo) -> &'static str {
+    match todo.done_by {
+        Some(_) => "✅",
+        None => "☑️",
+    }
+}
+
+/// The plain title of a chat. Private chats have none, their ids are positive while group ids
+/// are negative.
+pub fn chat_title(chat_id: i64, title: Option<&str>, texts: &Texts) -> String {
+    match title {
+        Some(title) => title.to_string(),
+        None if chat_id > 0 => texts.private_chat.to_string(),
+        None => fill(texts.unknown_chat, &[("id", &chat_id.to_string())]),
+    }
+}
+
+/// The interval of recurring todos, when a done one is due again and since when an open one
+/// is overdue.
+fn interval(interval_days: i64, texts: &Texts) -> String {
+    let template = if interval_days > 1 {
+        texts.interval_days
+    } else {
+        texts.interval_day
+    };
+
+    fill(template, &[("n", &interval_days.to_string())])
+}
+
+fn list_suffix(todo: &Todo, today: NaiveDate, texts: &Texts) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(interval_days) = todo.interval_days {
+        parts.push(interval(interval_days, texts));
+    }
+
+    match (todo.done_by, todo.interval_days) {
+        (Some(_), Some(interval_days)) if todo.scheduled_for < today => {
+            let next = todo.scheduled_for + Duration::days(interval_days);
+            parts.push(fill(
+                texts.next_date,
+                &[("date", &format_date(next, texts))],
+            ));
+        }
+        (None, _) if todo.scheduled_for < today => parts.push(fill(
+            texts.due_date,
+            &[("date", &format_date(todo.scheduled_for, texts))],
+        )),
+        _ => {}
+    }
+
+    if parts.is_empty() {
+        "".to_string()
+    } else {
+        format!("({})", parts.join(", "))
+    }
+}
+
+pub fn format_date(date: NaiveDate, texts: &Texts) -> String {
+    date.format(texts.date_format).to_string()
+}
+
+/// Escapes a todo description and strikes it through if done or makes it bold if overdue.
+pub fn format_description(description: &str, done: bool, overdue: bool) -> String {
+    let description = escape_html(description);
+
+    if done {
+        format!("<s>{}</s>", description)
+    } else if overdue {
+        format!("<b>{}</b>", description)
+    } else {
+        description
+    }
+}
+
+/// Escapes `text` for messages sent with the HTML parse mode.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Returns an HTML mention of a telegram user, which notifies them.
+pub fn mention(telegram_user_id: i64, name: &str) -> String {
+    format!(
+        r#"<a href="tg://user?id={}">{}</a>"#,
+        telegram_user_id,
+        escape_html(name)
+    )
+}
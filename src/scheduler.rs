@@ -0,0 +1,166 @@
+use crate::{
+    config::Config,
+    context::Context,
+    error::LeditError,
+    interval::{delete_one_time_todos, re_schedule_todos, send_digests, send_reminders},
+};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+use tokio::{
+    sync::watch,
+    time::{sleep, Duration},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Job {
+    ReScheduleTodos,
+    DeleteOneTimeTodos,
+    Reminders,
+    Digests,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    /// Run whenever the given duration has passed since the last run.
+    Every(ChronoDuration),
+    /// Run once a day at the given wall-clock time.
+    DailyAt(NaiveTime),
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Job {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Job::ReScheduleTodos => "re_schedule_todos",
+            Job::DeleteOneTimeTodos => "delete_one_time_todos",
+            Job::Reminders => "reminders",
+            Job::Digests => "digests",
+        }
+    }
+
+    async fn run(&self, ctx: Context<'_>) -> Result<(), LeditError> {
+        let (storage, messenger) = (ctx.storage, ctx.messenger);
+        let now = ctx.clock.now().with_timezone(&ctx.config.default_timezone);
+        let today = now.date_naive();
+
+        match self {
+            Job::ReScheduleTodos => re_schedule_todos(now, storage, messenger).await,
+            Job::DeleteOneTimeTodos => {
+                let retention_days = ctx.config.one_time_todo_retention_days;
+                delete_one_time_todos(retention_days, today, storage, messenger).await
+            }
+            Job::Reminders => send_reminders(today, storage, messenger).await,
+            Job::Digests => send_digests(today, storage, messenger).await,
+        }
+    }
+}
+
+impl Schedule {
+    /// Returns whether a job with this schedule and the given last run is due at `now`.
+    /// Missed runs (e.g. because the bot was down) are caught up with a single run.
+    pub fn is_due(&self, last_run_at: Option<DateTime<Utc>>, now: DateTime<Utc>, tz: Tz) -> bool {
+        match (self, last_run_at) {
+            (Schedule::Every(_), None) => true,
+            (Schedule::Every(every), Some(last_run_at)) => last_run_at + *every <= now,
+            (Schedule::DailyAt(time), last_run_at) => match last_occurrence(*time, tz, now) {
+                Some(occurrence) => !matches!(last_run_at, Some(v) if v >= occurrence),
+                None => false,
+            },
+        }
+    }
+}
+
+/// Returns the latest point in time before `now` at which the local time in `tz` was `time`.
+fn last_occurrence(time: NaiveTime, tz: Tz, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let local_date = now.with_timezone(&tz).date_naive();
+
+    [local_date, local_date - ChronoDuration::days(1)]
+        .iter()
+        .filter_map(|date| tz.from_local_datetime(&date.and_time(time)).earliest())
+        .map(|v| v.with_timezone(&Utc))
+        .find(|v| *v <= now)
+}
+
+pub fn registered_jobs(config: &Config) -> Vec<(Job, Schedule)> {
+    let every_tick = Schedule::Every(ChronoDuration::milliseconds(config.interval_ms as i64));
+    let mut jobs = vec![
+        (Job::ReScheduleTodos, every_tick),
+        (Job::DeleteOneTimeTodos, every_tick),
+    ];
+    if let Some(time) = config.reminder_time {
+        jobs.push((Job::Reminders, Schedule::DailyAt(time)));
+    }
+    if let Some(time) = config.digest_time {
+        jobs.push((Job::Digests, Schedule::DailyAt(time)));
+    }
+
+    jobs
+}
+
+pub async fn run_scheduler(
+    ctx: Context<'_>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), LeditError> {
+    let config = ctx.config;
+    let jobs = registered_jobs(config);
+
+    tracing::info!(
+        "scheduler tick: {} ms, jobs: {:?}",
+        config.interval_ms,
+        jobs
+    );
+
+    loop {
+        // only wait for shutdown while sleeping, so a started pass always completes
+        tokio::select! {
+            _ = sleep(Duration::from_millis(config.interval_ms)) => {},
+            _ = shutdown.changed() => break,
+        }
+
+        for (job, schedule) in &jobs {
+            if let Err(err) = run_job_if_due(*job, *schedule, ctx).await {
+                tracing::error!("failed to run job {}, err: {}", job, err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs `job` if it is due and no other instance is running it right now.
+/// The job lock is held until the run is recorded.
+pub async fn run_job_if_due(
+    job: Job,
+    schedule: Schedule,
+    ctx: Context<'_>,
+) -> Result<(), LeditError> {
+    let mut lock = match ctx.storage.lock_job(job.name()).await? {
+        Some(v) => v,
+        None => {
+            tracing::debug!("job {} is run by another instance", job);
+            return Ok(());
+        }
+    };
+
+    let last_run_at = lock.last_run_at().await?;
+    let now = ctx.clock.now();
+    if !schedule.is_due(last_run_at, now, ctx.config.default_timezone) {
+        return lock.release().await;
+    }
+
+    tracing::info!("run job {}", job);
+    let result = job.run(ctx).await;
+    match &result {
+        Ok(_) => tracing::info!("job {} done", job),
+        Err(err) => tracing::error!("job {} failed, err: {}", job, err),
+    }
+
+    lock.record_run(now, result.err().map(|err| err.to_string()))
+        .await
+}
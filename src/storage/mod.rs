@@ -0,0 +1,254 @@
+use crate::{
+    config::Config,
+    error::LeditError,
+    handler::{chat_member::ChatMember, todo::Todo, undo::UndoEntry},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+mod postgres;
+mod sqlite;
+
+pub use self::{postgres::PgStorage, sqlite::SqliteStorage};
+
+/// A todo together with the name of the chat member it is assigned to.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct AssignedTodo {
+    pub id: Uuid,
+    pub chat_id: i64,
+    pub description: String,
+
+    pub interval_days: Option<i64>,
+    pub assigned_user: Uuid,
+    pub scheduled_for: NaiveDate,
+    pub done_by: Option<Uuid>,
+
+    pub telegram_user_id: i64,
+    pub display_name: String,
+}
+
+/// A todo assigned to a telegram user together with the title of its chat.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct UserTodo {
+    pub id: Uuid,
+    pub chat_id: i64,
+    pub description: String,
+
+    pub interval_days: Option<i64>,
+    pub scheduled_for: NaiveDate,
+    pub done_by: Option<Uuid>,
+
+    pub chat_title: Option<String>,
+}
+
+/// Persistence of chat members, todos and job runs.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn migrate(&self) -> Result<(), LeditError>;
+
+    async fn close(&self);
+
+    async fn get_chat_member(
+        &self,
+        telegram_user_id: i64,
+        chat_id: i64,
+    ) -> Result<Option<ChatMember>, LeditError>;
+
+    /// Inserts the chat member or, if a member with the same telegram user and chat exists,
+    /// updates its names.
+    async fn upsert_chat_member(&self, chat_member: &ChatMember) -> Result<(), LeditError>;
+
+    /// Stores the title of a group chat.
+    async fn set_chat_title(&self, chat_id: i64, title: &str) -> Result<(), LeditError>;
+
+    async fn get_chat_title(&self, chat_id: i64) -> Result<Option<String>, LeditError>;
+
+    /// Returns the code of the language the chat chose.
+    async fn get_chat_language(&self, chat_id: i64) -> Result<Option<String>, LeditError>;
+
+    async fn set_chat_language(&self, chat_id: i64, language: &str) -> Result<(), LeditError>;
+
+    /// Whether todos can be assigned to the member who created them, true unless the chat
+    /// turned it off.
+    async fn get_assign_creator(&self, chat_id: i64) -> Result<bool, LeditError>;
+
+    async fn set_assign_creator(&self, chat_id: i64, enabled: bool) -> Result<(), LeditError>;
+
+    /// Whether deleting any todo of the chat asks for confirmation, not just recurring ones.
+    async fn get_confirm_deletes(&self, chat_id: i64) -> Result<bool, LeditError>;
+
+    async fn set_confirm_deletes(&self, chat_id: i64, enabled: bool) -> Result<(), LeditError>;
+
+    /// Returns the code of the permission the chat chose for the operation.
+    async fn get_chat_permission(
+        &self,
+        chat_id: i64,
+        operation: &str,
+    ) -> Result<Option<String>, LeditError>;
+
+    async fn set_chat_permission(
+        &self,
+        chat_id: i64,
+        operation: &str,
+        permission: &str,
+    ) -> Result<(), LeditError>;
+
+    /// Turns direct messages about assigned todos to the telegram user on or off.
+    async fn set_notifications(
+        &self,
+        telegram_user_id: i64,
+        enabled: bool,
+    ) -> Result<(), LeditError>;
+
+    async fn get_notifications(&self, telegram_user_id: i64) -> Result<bool, LeditError>;
+
+    /// Returns the telegram user of the chat member if they turned notifications on.
+    async fn get_notified_telegram_user_id(
+        &self,
+        chat_member_id: Uuid,
+    ) -> Result<Option<i64>, LeditError>;
+
+    async fn get_chat_member_ids(&self, chat_id: i64) -> Result<Vec<Uuid>, LeditError>;
+
+    async fn get_chat_members(&self, chat_id: i64) -> Result<Vec<ChatMember>, LeditError>;
+
+    async fn insert_todo(&self, todo: &Todo) -> Result<Todo, LeditError>;
+
+    /// Returns the todos of a chat in the order they are numbered in: one-time todos first,
+    /// then recurring ones by interval, each by description.
+    async fn get_sorted_todos(&self, chat_id: i64) -> Result<Vec<Todo>, LeditError>;
+
+    async fn get_todo(&self, id: Uuid) -> Result<Option<Todo>, LeditError>;
+
+    async fn delete_todo(&self, id: Uuid) -> Result<(), LeditError>;
+
+    async fn set_todo_done_by(&self, id: Uuid, done_by: Option<Uuid>) -> Result<(), LeditError>;
+
+    /// Inserts the todo or, if it still exists, sets all its fields back to the given ones.
+    async fn restore_todo(&self, todo: &Todo) -> Result<(), LeditError>;
+
+    /// Returns the todos of a chat to show for `today`: one-time todos scheduled until today
+    /// and recurring ones that are open and due or were scheduled for and done today.
+    async fn get_actionable_todos(
+        &self,
+        chat_id: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<AssignedTodo>, LeditError>;
+
+    /// Returns the actionable todos of all chats that are assigned to the telegram user,
+    /// ordered by chat title.
+    async fn get_todos_of_user(
+        &self,
+        telegram_user_id: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<UserTodo>, LeditError>;
+
+    /// Returns the open todos of all chats that were scheduled before `today`.
+    async fn get_overdue_todos(&self, today: NaiveDate) -> Result<Vec<AssignedTodo>, LeditError>;
+
+    /// Returns the ids of all chats with at least one todo.
+    async fn get_chat_ids(&self) -> Result<Vec<i64>, LeditError>;
+
+    /// Starts re-scheduling done recurring todos in a transaction.
+    async fn begin_re_scheduling(&self) -> Result<Box<dyn ReScheduling>, LeditError>;
+
+    /// Deletes done one-time todos scheduled at least `retention_days` before `today`
+    /// and returns them.
+    async fn delete_done_one_time_todos(
+        &self,
+        retention_days: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<Todo>, LeditError>;
+
+    /// Returns the id of the message showing the board of a chat.
+    async fn get_board_message_id(&self, chat_id: i64) -> Result<Option<i32>, LeditError>;
+
+    async fn set_board_message_id(&self, chat_id: i64, message_id: i32) -> Result<(), LeditError>;
+
+    async fn delete_board(&self, chat_id: i64) -> Result<(), LeditError>;
+
+    /// Returns the chats whose board was not refreshed by the periodic jobs on `today` yet.
+    async fn get_stale_board_chat_ids(&self, today: NaiveDate) -> Result<Vec<i64>, LeditError>;
+
+    async fn set_board_refreshed_on(
+        &self,
+        chat_id: i64,
+        today: NaiveDate,
+    ) -> Result<(), LeditError>;
+
+    async fn insert_undo_entry(&self, entry: &UndoEntry) -> Result<(), LeditError>;
+
+    /// Removes the latest undo entry of the chat recorded since `since` and returns it.
+    async fn take_last_undo_entry(
+        &self,
+        chat_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Option<UndoEntry>, LeditError>;
+
+    /// Deletes the undo entries of all chats recorded before `before`.
+    async fn delete_undo_entries_before(&self, before: DateTime<Utc>) -> Result<(), LeditError>;
+
+    /// Takes the lock of the job `name`, returns `None` if another instance holds it.
+    async fn lock_job(&self, name: &str) -> Result<Option<Box<dyn JobLock>>, LeditError>;
+}
+
+/// Exclusive access to a job and its run history, released by `record_run` or `release`.
+#[async_trait]
+pub trait JobLock: Send {
+    async fn last_run_at(&mut self) -> Result<Option<DateTime<Utc>>, LeditError>;
+
+    /// Stores a run of the job started at `run_at`, failed if `error` is set.
+    async fn record_run(
+        self: Box<Self>,
+        run_at: DateTime<Utc>,
+        error: Option<String>,
+    ) -> Result<(), LeditError>;
+
+    async fn release(self: Box<Self>) -> Result<(), LeditError>;
+}
+
+/// A transaction re-scheduling done recurring todos, committed by `commit` and rolled back
+/// when dropped.
+#[async_trait]
+pub trait ReScheduling: Send {
+    /// Returns the done recurring todos whose interval has passed by `today` and keeps others
+    /// from changing them until the transaction ends.
+    async fn get_todos(&mut self, today: NaiveDate) -> Result<Vec<Todo>, LeditError>;
+
+    /// Returns the ids of the members of each chat, chats without members are missing.
+    async fn get_chat_member_ids(
+        &mut self,
+        chat_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Uuid>>, LeditError>;
+
+    /// Returns whether todos can be assigned to their creator for each chat, chats that
+    /// never changed it are missing.
+    async fn get_assign_creator(
+        &mut self,
+        chat_ids: &[i64],
+    ) -> Result<HashMap<i64, bool>, LeditError>;
+
+    /// Marks the given todos as open again, scheduled for `today` and assigned to the paired
+    /// user.
+    async fn commit(
+        self: Box<Self>,
+        assignments: &[(Uuid, Uuid)],
+        today: NaiveDate,
+    ) -> Result<(), LeditError>;
+}
+
+/// Connects to the storage backend matching the scheme of `DATABASE_URL`.
+pub async fn connect(config: &Config) -> Result<Box<dyn Storage>, LeditError> {
+    if config.database_url.starts_with("sqlite:") {
+        Ok(Box::new(
+            SqliteStorage::connect(&config.database_url, config.pool_max_connections).await?,
+        ))
+    } else {
+        Ok(Box::new(
+            PgStorage::connect(&config.database_url, config.pool_max_connections).await?,
+        ))
+    }
+}
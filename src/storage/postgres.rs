@@ -0,0 +1,927 @@
+use super::{AssignedTodo, JobLock, ReScheduling, Storage, UserTodo};
+use crate::{
+    error::LeditError,
+    handler::{chat_member::ChatMember, todo::Todo, undo::UndoEntry},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use itertools::Itertools;
+use sqlx::{postgres::PgPoolOptions, types::Json, Pool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// first key of all job advisory locks, the second one is derived from the job name
+const JOB_LOCK_NAMESPACE: i32 = 7_000;
+
+pub struct PgStorage {
+    pool: Pool<Postgres>,
+}
+
+impl PgStorage {
+    pub async fn connect(url: &str, max_connections: u32) -> Result<Self, LeditError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await?;
+
+        Ok(PgStorage { pool })
+    }
+}
+
+#[async_trait]
+impl Storage for PgStorage {
+    async fn migrate(&self) -> Result<(), LeditError> {
+        sqlx::migrate!("./migrations/postgres")
+            .run(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    async fn get_chat_member(
+        &self,
+        telegram_user_id: i64,
+        chat_id: i64,
+    ) -> Result<Option<ChatMember>, LeditError> {
+        sqlx::query_as!(
+            ChatMember,
+            "select * from chat_members where telegram_user_id = $1 and chat_id = $2",
+            telegram_user_id,
+            chat_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn upsert_chat_member(&self, chat_member: &ChatMember) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into chat_members (
+                    id,
+                    telegram_user_id,
+                    chat_id,
+                    display_name,
+                    todo_weight,
+
+                    username,
+                    first_name,
+                    last_name
+                )
+                values ( $1, $2, $3, $4, $5, $6, $7, $8 )
+                on conflict (chat_id, telegram_user_id) do update set
+                    display_name = excluded.display_name,
+                    username = excluded.username,
+                    first_name = excluded.first_name,
+                    last_name = excluded.last_name
+            "#,
+            chat_member.id,
+            chat_member.telegram_user_id,
+            chat_member.chat_id,
+            chat_member.display_name,
+            chat_member.todo_weight,
+            chat_member.username,
+            chat_member.first_name,
+            chat_member.last_name,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_chat_title(&self, chat_id: i64, title: &str) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into chats (id, title)
+                values ( $1, $2 )
+                on conflict (id) do update set title = excluded.title
+            "#,
+            chat_id,
+            title
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_chat_title(&self, chat_id: i64) -> Result<Option<String>, LeditError> {
+        let title = sqlx::query!("select title from chats where id = $1", chat_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|v| v.title);
+
+        Ok(title)
+    }
+
+    async fn get_chat_language(&self, chat_id: i64) -> Result<Option<String>, LeditError> {
+        let language = sqlx::query!(
+            "select language from chat_settings where chat_id = $1",
+            chat_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|v| v.language);
+
+        Ok(language)
+    }
+
+    async fn set_chat_language(&self, chat_id: i64, language: &str) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into chat_settings (chat_id, language)
+                values ( $1, $2 )
+                on conflict (chat_id) do update set language = excluded.language
+            "#,
+            chat_id,
+            language
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_assign_creator(&self, chat_id: i64) -> Result<bool, LeditError> {
+        let assign_creator = sqlx::query!(
+            "select assign_creator from chat_settings where chat_id = $1",
+            chat_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|v| v.assign_creator)
+        .unwrap_or(true);
+
+        Ok(assign_creator)
+    }
+
+    async fn set_assign_creator(&self, chat_id: i64, enabled: bool) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into chat_settings (chat_id, assign_creator)
+                values ( $1, $2 )
+                on conflict (chat_id) do update set assign_creator = excluded.assign_creator
+            "#,
+            chat_id,
+            enabled
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_confirm_deletes(&self, chat_id: i64) -> Result<bool, LeditError> {
+        let confirm_deletes = sqlx::query!(
+            "select confirm_deletes from chat_settings where chat_id = $1",
+            chat_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|v| v.confirm_deletes)
+        .unwrap_or(false);
+
+        Ok(confirm_deletes)
+    }
+
+    async fn set_confirm_deletes(&self, chat_id: i64, enabled: bool) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into chat_settings (chat_id, confirm_deletes)
+                values ( $1, $2 )
+                on conflict (chat_id) do update set confirm_deletes = excluded.confirm_deletes
+            "#,
+            chat_id,
+            enabled
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_chat_permission(
+        &self,
+        chat_id: i64,
+        operation: &str,
+    ) -> Result<Option<String>, LeditError> {
+        let permission = sqlx::query!(
+            "select permission from chat_permissions where chat_id = $1 and operation = $2",
+            chat_id,
+            operation
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|v| v.permission);
+
+        Ok(permission)
+    }
+
+    async fn set_chat_permission(
+        &self,
+        chat_id: i64,
+        operation: &str,
+        permission: &str,
+    ) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into chat_permissions (chat_id, operation, permission)
+                values ( $1, $2, $3 )
+                on conflict (chat_id, operation) do update set permission = excluded.permission
+            "#,
+            chat_id,
+            operation,
+            permission
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_notifications(
+        &self,
+        telegram_user_id: i64,
+        enabled: bool,
+    ) -> Result<(), LeditError> {
+        if enabled {
+            sqlx::query!(
+                r#"
+                    insert into notification_subscribers (telegram_user_id)
+                    values ( $1 )
+                    on conflict do nothing
+                "#,
+                telegram_user_id
+            )
+            .execute(&self.pool)
+            .await?;
+        } else {
+            sqlx::query!(
+                "delete from notification_subscribers where telegram_user_id = $1",
+                telegram_user_id
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_notifications(&self, telegram_user_id: i64) -> Result<bool, LeditError> {
+        let subscriber = sqlx::query!(
+            "select telegram_user_id from notification_subscribers where telegram_user_id = $1",
+            telegram_user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(subscriber.is_some())
+    }
+
+    async fn get_notified_telegram_user_id(
+        &self,
+        chat_member_id: Uuid,
+    ) -> Result<Option<i64>, LeditError> {
+        let telegram_user_id = sqlx::query!(
+            r#"
+                select 
+                    n.telegram_user_id 
+                from 
+                    chat_members as c
+                join 
+                    notification_subscribers as n on n.telegram_user_id = c.telegram_user_id
+                where 
+                    c.id = $1
+            "#,
+            chat_member_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|v| v.telegram_user_id);
+
+        Ok(telegram_user_id)
+    }
+
+    async fn get_chat_member_ids(&self, chat_id: i64) -> Result<Vec<Uuid>, LeditError> {
+        let ids = sqlx::query!(r#"select id from chat_members where chat_id = $1"#, chat_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|v| v.id)
+            .collect();
+
+        Ok(ids)
+    }
+
+    async fn get_chat_members(&self, chat_id: i64) -> Result<Vec<ChatMember>, LeditError> {
+        sqlx::query_as!(
+            ChatMember,
+            "select * from chat_members where chat_id = $1",
+            chat_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn insert_todo(&self, todo: &Todo) -> Result<Todo, LeditError> {
+        sqlx::query_as!(
+            Todo,
+            r#"
+                insert into todos (
+                    id,
+                    chat_id,
+                    description,
+                    
+                    interval_days,
+                    assigned_user,
+                    scheduled_for,
+                    done_by,
+                    created_by,
+                    created_at
+                )
+                values ( $1, $2, $3, $4, $5, $6, $7, $8, $9 )
+                RETURNING *
+            "#,
+            todo.id,
+            todo.chat_id,
+            todo.description,
+            todo.interval_days,
+            todo.assigned_user,
+            todo.scheduled_for,
+            todo.done_by,
+            todo.created_by,
+            todo.created_at,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_sorted_todos(&self, chat_id: i64) -> Result<Vec<Todo>, LeditError> {
+        sqlx::query_as!(
+            Todo,
+            r#"
+                select 
+                    * 
+                from 
+                    todos 
+                where 
+                    chat_id = $1 
+                order by 
+                    interval_days is null desc, interval_days asc, description asc
+            "#,
+            chat_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_todo(&self, id: Uuid) -> Result<Option<Todo>, LeditError> {
+        sqlx::query_as!(Todo, "select * from todos where id = $1", id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn delete_todo(&self, id: Uuid) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                delete from todos where id = $1
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_todo_done_by(&self, id: Uuid, done_by: Option<Uuid>) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"update todos set done_by = $1 where id = $2"#,
+            done_by,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn restore_todo(&self, todo: &Todo) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into todos (
+                    id,
+                    chat_id,
+                    description,
+                    
+                    interval_days,
+                    assigned_user,
+                    scheduled_for,
+                    done_by,
+                    created_by,
+                    created_at
+                )
+                values ( $1, $2, $3, $4, $5, $6, $7, $8, $9 )
+                on conflict (id) do update set
+                    chat_id = excluded.chat_id,
+                    description = excluded.description,
+                    interval_days = excluded.interval_days,
+                    assigned_user = excluded.assigned_user,
+                    scheduled_for = excluded.scheduled_for,
+                    done_by = excluded.done_by,
+                    created_by = excluded.created_by,
+                    created_at = excluded.created_at
+            "#,
+            todo.id,
+            todo.chat_id,
+            todo.description,
+            todo.interval_days,
+            todo.assigned_user,
+            todo.scheduled_for,
+            todo.done_by,
+            todo.created_by,
+            todo.created_at,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_actionable_todos(
+        &self,
+        chat_id: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<AssignedTodo>, LeditError> {
+        sqlx::query_as!(
+            AssignedTodo,
+            r#"
+                select 
+                    t.id,
+                    t.chat_id,
+                    t.description,
+                    t.interval_days,
+                    t.assigned_user,
+                    t.scheduled_for,
+                    t.done_by,
+
+                    c.telegram_user_id,
+                    c.display_name
+                from 
+                    todos as t
+                join 
+                    chat_members as c on c.id = t.assigned_user
+                where 
+                    t.chat_id = $1
+                    and c.chat_id = $1
+                    and 
+                        (
+                            (t.interval_days is null and t.scheduled_for <= $2)
+                            or 
+                            (   
+                                t.interval_days is not null
+                                and (
+                                    (t.scheduled_for <= $2 and t.done_by is null) 
+                                    or (t.scheduled_for = $2 and t.done_by is not null))
+                            )
+                        )
+                order by 
+                    t.done_by asc, t.description asc
+            "#,
+            chat_id,
+            today,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_todos_of_user(
+        &self,
+        telegram_user_id: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<UserTodo>, LeditError> {
+        sqlx::query_as!(
+            UserTodo,
+            r#"
+                select 
+                    t.id,
+                    t.chat_id,
+                    t.description,
+                    t.interval_days,
+                    t.scheduled_for,
+                    t.done_by,
+
+                    ch.title as "chat_title?"
+                from 
+                    todos as t
+                join 
+                    chat_members as c on c.id = t.assigned_user
+                left join 
+                    chats as ch on ch.id = t.chat_id
+                where 
+                    c.telegram_user_id = $1
+                    and 
+                        (
+                            (t.interval_days is null and t.scheduled_for <= $2)
+                            or 
+                            (   
+                                t.interval_days is not null
+                                and (
+                                    (t.scheduled_for <= $2 and t.done_by is null) 
+                                    or (t.scheduled_for = $2 and t.done_by is not null))
+                            )
+                        )
+                order by 
+                    ch.title is null asc, ch.title asc, t.chat_id asc,
+                    t.done_by is not null asc, t.description asc
+            "#,
+            telegram_user_id,
+            today,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_overdue_todos(&self, today: NaiveDate) -> Result<Vec<AssignedTodo>, LeditError> {
+        sqlx::query_as!(
+            AssignedTodo,
+            r#"
+                select
+                    t.id,
+                    t.chat_id,
+                    t.description,
+                    t.interval_days,
+                    t.assigned_user,
+                    t.scheduled_for,
+                    t.done_by,
+
+                    c.telegram_user_id,
+                    c.display_name
+                from
+                    todos as t
+                join
+                    chat_members as c on c.id = t.assigned_user
+                where
+                    t.done_by is null
+                    and t.scheduled_for < $1
+                order by
+                    t.chat_id, c.display_name, t.description
+            "#,
+            today,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_chat_ids(&self) -> Result<Vec<i64>, LeditError> {
+        let chat_ids = sqlx::query!("select distinct chat_id from todos")
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|v| v.chat_id)
+            .collect();
+
+        Ok(chat_ids)
+    }
+
+    async fn begin_re_scheduling(&self) -> Result<Box<dyn ReScheduling>, LeditError> {
+        Ok(Box::new(PgReScheduling {
+            tx: self.pool.begin().await?,
+        }))
+    }
+
+    async fn delete_done_one_time_todos(
+        &self,
+        retention_days: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<Todo>, LeditError> {
+        sqlx::query_as!(
+            Todo,
+            r#"
+                delete from 
+                    todos 
+                where 
+                    done_by is not null 
+                    and interval_days is null 
+                    and scheduled_for + $1::int4 <= $2
+                returning *
+            "#,
+            retention_days as i32,
+            today
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_board_message_id(&self, chat_id: i64) -> Result<Option<i32>, LeditError> {
+        let message_id = sqlx::query!("select message_id from boards where chat_id = $1", chat_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|v| v.message_id);
+
+        Ok(message_id)
+    }
+
+    async fn set_board_message_id(&self, chat_id: i64, message_id: i32) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into boards (chat_id, message_id)
+                values ( $1, $2 )
+                on conflict (chat_id) do update set message_id = excluded.message_id
+            "#,
+            chat_id,
+            message_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_board(&self, chat_id: i64) -> Result<(), LeditError> {
+        sqlx::query!("delete from boards where chat_id = $1", chat_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_stale_board_chat_ids(&self, today: NaiveDate) -> Result<Vec<i64>, LeditError> {
+        let chat_ids = sqlx::query!(
+            r#"
+                select chat_id 
+                from boards 
+                where refreshed_on is null or refreshed_on < $1
+            "#,
+            today
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|v| v.chat_id)
+        .collect();
+
+        Ok(chat_ids)
+    }
+
+    async fn set_board_refreshed_on(
+        &self,
+        chat_id: i64,
+        today: NaiveDate,
+    ) -> Result<(), LeditError> {
+        sqlx::query!(
+            "update boards set refreshed_on = $2 where chat_id = $1",
+            chat_id,
+            today
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_undo_entry(&self, entry: &UndoEntry) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into undo_journal (id, chat_id, "action", todo, recorded_at)
+                values ( $1, $2, $3, $4, $5 )
+            "#,
+            entry.id,
+            entry.chat_id,
+            entry.action,
+            Json(&entry.todo) as _,
+            entry.recorded_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn take_last_undo_entry(
+        &self,
+        chat_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Option<UndoEntry>, LeditError> {
+        let entry = sqlx::query!(
+            r#"
+                delete from 
+                    undo_journal 
+                where 
+                    id = (
+                        select id from undo_journal 
+                        where chat_id = $1 and recorded_at >= $2 
+                        order by recorded_at desc 
+                        limit 1
+                    )
+                returning id, chat_id, "action", todo as "todo: Json<Todo>", recorded_at
+            "#,
+            chat_id,
+            since
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|v| UndoEntry {
+            id: v.id,
+            chat_id: v.chat_id,
+            action: v.action,
+            todo: v.todo.0,
+            recorded_at: v.recorded_at,
+        });
+
+        Ok(entry)
+    }
+
+    async fn delete_undo_entries_before(&self, before: DateTime<Utc>) -> Result<(), LeditError> {
+        sqlx::query!("delete from undo_journal where recorded_at < $1", before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn lock_job(&self, name: &str) -> Result<Option<Box<dyn JobLock>>, LeditError> {
+        let mut tx = self.pool.begin().await?;
+
+        let lock = sqlx::query!(
+            r#"select pg_try_advisory_xact_lock($1, hashtext($2)) as "locked!""#,
+            JOB_LOCK_NAMESPACE,
+            name
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        if !lock.locked {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        Ok(Some(Box::new(PgJobLock {
+            tx,
+            name: name.to_string(),
+        })))
+    }
+}
+
+/// Holds a transaction scoped advisory lock, so other instances skip the job until it
+/// is committed or rolled back.
+struct PgJobLock {
+    tx: Transaction<'static, Postgres>,
+    name: String,
+}
+
+#[async_trait]
+impl JobLock for PgJobLock {
+    async fn last_run_at(&mut self) -> Result<Option<DateTime<Utc>>, LeditError> {
+        let last_run_at = sqlx::query!(
+            "select last_run_at from job_runs where name = $1",
+            self.name
+        )
+        .fetch_optional(&mut self.tx)
+        .await?
+        .map(|v| v.last_run_at);
+
+        Ok(last_run_at)
+    }
+
+    async fn record_run(
+        mut self: Box<Self>,
+        run_at: DateTime<Utc>,
+        error: Option<String>,
+    ) -> Result<(), LeditError> {
+        sqlx::query!(
+            r#"
+                insert into job_runs (name, last_run_at, last_success_at, last_error)
+                values ( $1, $2, $3, $4 )
+                on conflict (name) do update set
+                    last_run_at = excluded.last_run_at,
+                    last_success_at = coalesce(excluded.last_success_at, job_runs.last_success_at),
+                    last_error = excluded.last_error
+            "#,
+            self.name,
+            run_at,
+            error.is_none().then_some(run_at),
+            error,
+        )
+        .execute(&mut self.tx)
+        .await?;
+
+        self.tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn release(self: Box<Self>) -> Result<(), LeditError> {
+        // roll back explicitly, a dropped transaction releases the lock only when its
+        // connection is used the next time
+        self.tx.rollback().await?;
+
+        Ok(())
+    }
+}
+
+/// Locks the todos to re-schedule, so a concurrent instance or check does not change them
+/// in between.
+struct PgReScheduling {
+    tx: Transaction<'static, Postgres>,
+}
+
+#[async_trait]
+impl ReScheduling for PgReScheduling {
+    async fn get_todos(&mut self, today: NaiveDate) -> Result<Vec<Todo>, LeditError> {
+        sqlx::query_as!(
+            Todo,
+            r#"
+                select *
+                from todos
+                where 
+                    interval_days is not null
+                    and done_by is not null
+                    and scheduled_for + interval_days::int4 <= $1
+                for update
+            "#,
+            today
+        )
+        .fetch_all(&mut self.tx)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_chat_member_ids(
+        &mut self,
+        chat_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Uuid>>, LeditError> {
+        let members = sqlx::query!(
+            "select id, chat_id from chat_members where chat_id = any($1)",
+            chat_ids
+        )
+        .fetch_all(&mut self.tx)
+        .await?
+        .into_iter()
+        .map(|v| (v.chat_id, v.id))
+        .into_group_map();
+
+        Ok(members)
+    }
+
+    async fn get_assign_creator(
+        &mut self,
+        chat_ids: &[i64],
+    ) -> Result<HashMap<i64, bool>, LeditError> {
+        let assign_creator = sqlx::query!(
+            "select chat_id, assign_creator from chat_settings where chat_id = any($1)",
+            chat_ids
+        )
+        .fetch_all(&mut self.tx)
+        .await?
+        .into_iter()
+        .map(|v| (v.chat_id, v.assign_creator))
+        .collect();
+
+        Ok(assign_creator)
+    }
+
+    async fn commit(
+        mut self: Box<Self>,
+        assignments: &[(Uuid, Uuid)],
+        today: NaiveDate,
+    ) -> Result<(), LeditError> {
+        let (todo_ids, assigned_users): (Vec<Uuid>, Vec<Uuid>) =
+            assignments.iter().cloned().unzip();
+
+        sqlx::query!(
+            r#"
+                update todos
+                set
+                    done_by = null,
+                    scheduled_for = $3,
+                    assigned_user = u.assigned_user
+                from 
+                    unnest($1::uuid[], $2::uuid[]) as u(id, assigned_user)
+                where 
+                    todos.id = u.id
+            "#,
+            &todo_ids[..],
+            &assigned_users[..],
+            today
+        )
+        .execute(&mut self.tx)
+        .await?;
+
+        self.tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,910 @@
+//! The queries here are checked at runtime only, the offline query data of sqlx
+//! covers the postgres queries.
+
+use super::{AssignedTodo, JobLock, ReScheduling, Storage, UserTodo};
+use crate::{
+    error::LeditError,
+    handler::{chat_member::ChatMember, todo::Todo, undo::UndoEntry},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use itertools::Itertools;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    types::Json,
+    Pool, Sqlite, Transaction,
+};
+use std::{collections::HashMap, str::FromStr};
+use uuid::Uuid;
+
+pub struct SqliteStorage {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteStorage {
+    /// Connects to the database at `url` (e.g. `sqlite://ledit.db`), creating it if needed.
+    pub async fn connect(url: &str, max_connections: u32) -> Result<Self, LeditError> {
+        let options = SqliteConnectOptions::from_str(url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+
+        // every connection to an in-memory database opens a new one, so keep a single one forever
+        let pool = if url.contains(":memory:") {
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            SqlitePoolOptions::new().max_connections(max_connections)
+        }
+        .connect_with(options)
+        .await?;
+
+        Ok(SqliteStorage { pool })
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn migrate(&self) -> Result<(), LeditError> {
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    async fn get_chat_member(
+        &self,
+        telegram_user_id: i64,
+        chat_id: i64,
+    ) -> Result<Option<ChatMember>, LeditError> {
+        sqlx::query_as("select * from chat_members where telegram_user_id = ? and chat_id = ?")
+            .bind(telegram_user_id)
+            .bind(chat_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn upsert_chat_member(&self, chat_member: &ChatMember) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into chat_members (
+                    id,
+                    telegram_user_id,
+                    chat_id,
+                    display_name,
+                    todo_weight,
+
+                    username,
+                    first_name,
+                    last_name
+                )
+                values ( ?, ?, ?, ?, ?, ?, ?, ? )
+                on conflict (chat_id, telegram_user_id) do update set
+                    display_name = excluded.display_name,
+                    username = excluded.username,
+                    first_name = excluded.first_name,
+                    last_name = excluded.last_name
+            "#,
+        )
+        .bind(chat_member.id)
+        .bind(chat_member.telegram_user_id)
+        .bind(chat_member.chat_id)
+        .bind(&chat_member.display_name)
+        .bind(chat_member.todo_weight)
+        .bind(&chat_member.username)
+        .bind(&chat_member.first_name)
+        .bind(&chat_member.last_name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_chat_title(&self, chat_id: i64, title: &str) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into chats (id, title)
+                values ( ?, ? )
+                on conflict (id) do update set title = excluded.title
+            "#,
+        )
+        .bind(chat_id)
+        .bind(title)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_chat_title(&self, chat_id: i64) -> Result<Option<String>, LeditError> {
+        let title = sqlx::query_as::<_, (String,)>("select title from chats where id = ?")
+            .bind(chat_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|(title,)| title);
+
+        Ok(title)
+    }
+
+    async fn get_chat_language(&self, chat_id: i64) -> Result<Option<String>, LeditError> {
+        let language =
+            sqlx::query_as::<_, (String,)>("select language from chat_settings where chat_id = ?")
+                .bind(chat_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .map(|(language,)| language);
+
+        Ok(language)
+    }
+
+    async fn set_chat_language(&self, chat_id: i64, language: &str) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into chat_settings (chat_id, language)
+                values ( ?, ? )
+                on conflict (chat_id) do update set language = excluded.language
+            "#,
+        )
+        .bind(chat_id)
+        .bind(language)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_assign_creator(&self, chat_id: i64) -> Result<bool, LeditError> {
+        let assign_creator = sqlx::query_as::<_, (bool,)>(
+            "select assign_creator from chat_settings where chat_id = ?",
+        )
+        .bind(chat_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|(assign_creator,)| assign_creator)
+        .unwrap_or(true);
+
+        Ok(assign_creator)
+    }
+
+    async fn set_assign_creator(&self, chat_id: i64, enabled: bool) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into chat_settings (chat_id, assign_creator)
+                values ( ?, ? )
+                on conflict (chat_id) do update set assign_creator = excluded.assign_creator
+            "#,
+        )
+        .bind(chat_id)
+        .bind(enabled)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_confirm_deletes(&self, chat_id: i64) -> Result<bool, LeditError> {
+        let confirm_deletes = sqlx::query_as::<_, (bool,)>(
+            "select confirm_deletes from chat_settings where chat_id = ?",
+        )
+        .bind(chat_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|(confirm_deletes,)| confirm_deletes)
+        .unwrap_or(false);
+
+        Ok(confirm_deletes)
+    }
+
+    async fn set_confirm_deletes(&self, chat_id: i64, enabled: bool) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into chat_settings (chat_id, confirm_deletes)
+                values ( ?, ? )
+                on conflict (chat_id) do update set confirm_deletes = excluded.confirm_deletes
+            "#,
+        )
+        .bind(chat_id)
+        .bind(enabled)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_chat_permission(
+        &self,
+        chat_id: i64,
+        operation: &str,
+    ) -> Result<Option<String>, LeditError> {
+        let permission = sqlx::query_as::<_, (String,)>(
+            "select permission from chat_permissions where chat_id = ? and operation = ?",
+        )
+        .bind(chat_id)
+        .bind(operation)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|(permission,)| permission);
+
+        Ok(permission)
+    }
+
+    async fn set_chat_permission(
+        &self,
+        chat_id: i64,
+        operation: &str,
+        permission: &str,
+    ) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into chat_permissions (chat_id, operation, permission)
+                values ( ?, ?, ? )
+                on conflict (chat_id, operation) do update set permission = excluded.permission
+            "#,
+        )
+        .bind(chat_id)
+        .bind(operation)
+        .bind(permission)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_notifications(
+        &self,
+        telegram_user_id: i64,
+        enabled: bool,
+    ) -> Result<(), LeditError> {
+        let query = if enabled {
+            r#"
+                insert into notification_subscribers (telegram_user_id)
+                values ( ? )
+                on conflict do nothing
+            "#
+        } else {
+            "delete from notification_subscribers where telegram_user_id = ?"
+        };
+
+        sqlx::query(query)
+            .bind(telegram_user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_notifications(&self, telegram_user_id: i64) -> Result<bool, LeditError> {
+        let subscriber = sqlx::query_as::<_, (i64,)>(
+            "select telegram_user_id from notification_subscribers where telegram_user_id = ?",
+        )
+        .bind(telegram_user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(subscriber.is_some())
+    }
+
+    async fn get_notified_telegram_user_id(
+        &self,
+        chat_member_id: Uuid,
+    ) -> Result<Option<i64>, LeditError> {
+        let telegram_user_id = sqlx::query_as::<_, (i64,)>(
+            r#"
+                select 
+                    n.telegram_user_id 
+                from 
+                    chat_members as c
+                join 
+                    notification_subscribers as n on n.telegram_user_id = c.telegram_user_id
+                where 
+                    c.id = ?
+            "#,
+        )
+        .bind(chat_member_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|(telegram_user_id,)| telegram_user_id);
+
+        Ok(telegram_user_id)
+    }
+
+    async fn get_chat_member_ids(&self, chat_id: i64) -> Result<Vec<Uuid>, LeditError> {
+        let ids = sqlx::query_as::<_, (Uuid,)>("select id from chat_members where chat_id = ?")
+            .bind(chat_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(id,)| id)
+            .collect();
+
+        Ok(ids)
+    }
+
+    async fn get_chat_members(&self, chat_id: i64) -> Result<Vec<ChatMember>, LeditError> {
+        sqlx::query_as("select * from chat_members where chat_id = ?")
+            .bind(chat_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn insert_todo(&self, todo: &Todo) -> Result<Todo, LeditError> {
+        sqlx::query_as(
+            r#"
+                insert into todos (
+                    id,
+                    chat_id,
+                    description,
+                    
+                    interval_days,
+                    assigned_user,
+                    scheduled_for,
+                    done_by,
+                    created_by,
+                    created_at
+                )
+                values ( ?, ?, ?, ?, ?, ?, ?, ?, ? )
+                returning *
+            "#,
+        )
+        .bind(todo.id)
+        .bind(todo.chat_id)
+        .bind(&todo.description)
+        .bind(todo.interval_days)
+        .bind(todo.assigned_user)
+        .bind(todo.scheduled_for)
+        .bind(todo.done_by)
+        .bind(todo.created_by)
+        .bind(todo.created_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_sorted_todos(&self, chat_id: i64) -> Result<Vec<Todo>, LeditError> {
+        sqlx::query_as(
+            r#"
+                select 
+                    * 
+                from 
+                    todos 
+                where 
+                    chat_id = ? 
+                order by 
+                    interval_days is null desc, interval_days asc, description asc
+            "#,
+        )
+        .bind(chat_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_todo(&self, id: Uuid) -> Result<Option<Todo>, LeditError> {
+        sqlx::query_as("select * from todos where id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn delete_todo(&self, id: Uuid) -> Result<(), LeditError> {
+        sqlx::query("delete from todos where id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_todo_done_by(&self, id: Uuid, done_by: Option<Uuid>) -> Result<(), LeditError> {
+        sqlx::query("update todos set done_by = ? where id = ?")
+            .bind(done_by)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn restore_todo(&self, todo: &Todo) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into todos (
+                    id,
+                    chat_id,
+                    description,
+                    
+                    interval_days,
+                    assigned_user,
+                    scheduled_for,
+                    done_by,
+                    created_by,
+                    created_at
+                )
+                values ( ?, ?, ?, ?, ?, ?, ?, ?, ? )
+                on conflict (id) do update set
+                    chat_id = excluded.chat_id,
+                    description = excluded.description,
+                    interval_days = excluded.interval_days,
+                    assigned_user = excluded.assigned_user,
+                    scheduled_for = excluded.scheduled_for,
+                    done_by = excluded.done_by,
+                    created_by = excluded.created_by,
+                    created_at = excluded.created_at
+            "#,
+        )
+        .bind(todo.id)
+        .bind(todo.chat_id)
+        .bind(&todo.description)
+        .bind(todo.interval_days)
+        .bind(todo.assigned_user)
+        .bind(todo.scheduled_for)
+        .bind(todo.done_by)
+        .bind(todo.created_by)
+        .bind(todo.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_actionable_todos(
+        &self,
+        chat_id: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<AssignedTodo>, LeditError> {
+        // sqlite sorts nulls first, postgres last
+        sqlx::query_as(
+            r#"
+                select 
+                    t.id,
+                    t.chat_id,
+                    t.description,
+                    t.interval_days,
+                    t.assigned_user,
+                    t.scheduled_for,
+                    t.done_by,
+
+                    c.telegram_user_id,
+                    c.display_name
+                from 
+                    todos as t
+                join 
+                    chat_members as c on c.id = t.assigned_user
+                where 
+                    t.chat_id = ?1
+                    and c.chat_id = ?1
+                    and 
+                        (
+                            (t.interval_days is null and t.scheduled_for <= ?2)
+                            or 
+                            (   
+                                t.interval_days is not null
+                                and (
+                                    (t.scheduled_for <= ?2 and t.done_by is null) 
+                                    or (t.scheduled_for = ?2 and t.done_by is not null))
+                            )
+                        )
+                order by 
+                    t.done_by is null asc, t.done_by asc, t.description asc
+            "#,
+        )
+        .bind(chat_id)
+        .bind(today)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_todos_of_user(
+        &self,
+        telegram_user_id: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<UserTodo>, LeditError> {
+        sqlx::query_as(
+            r#"
+                select 
+                    t.id,
+                    t.chat_id,
+                    t.description,
+                    t.interval_days,
+                    t.scheduled_for,
+                    t.done_by,
+
+                    ch.title as chat_title
+                from 
+                    todos as t
+                join 
+                    chat_members as c on c.id = t.assigned_user
+                left join 
+                    chats as ch on ch.id = t.chat_id
+                where 
+                    c.telegram_user_id = ?1
+                    and 
+                        (
+                            (t.interval_days is null and t.scheduled_for <= ?2)
+                            or 
+                            (   
+                                t.interval_days is not null
+                                and (
+                                    (t.scheduled_for <= ?2 and t.done_by is null) 
+                                    or (t.scheduled_for = ?2 and t.done_by is not null))
+                            )
+                        )
+                order by 
+                    ch.title is null asc, ch.title asc, t.chat_id asc,
+                    t.done_by is not null asc, t.description asc
+            "#,
+        )
+        .bind(telegram_user_id)
+        .bind(today)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_overdue_todos(&self, today: NaiveDate) -> Result<Vec<AssignedTodo>, LeditError> {
+        sqlx::query_as(
+            r#"
+                select
+                    t.id,
+                    t.chat_id,
+                    t.description,
+                    t.interval_days,
+                    t.assigned_user,
+                    t.scheduled_for,
+                    t.done_by,
+
+                    c.telegram_user_id,
+                    c.display_name
+                from
+                    todos as t
+                join
+                    chat_members as c on c.id = t.assigned_user
+                where
+                    t.done_by is null
+                    and t.scheduled_for < ?
+                order by
+                    t.chat_id, c.display_name, t.description
+            "#,
+        )
+        .bind(today)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_chat_ids(&self) -> Result<Vec<i64>, LeditError> {
+        let chat_ids = sqlx::query_as::<_, (i64,)>("select distinct chat_id from todos")
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(chat_id,)| chat_id)
+            .collect();
+
+        Ok(chat_ids)
+    }
+
+    async fn begin_re_scheduling(&self) -> Result<Box<dyn ReScheduling>, LeditError> {
+        let mut tx = self.pool.begin().await?;
+
+        // sqlx starts deferred transactions, which only take the write lock on their first
+        // write. A write after reading fails if another connection wrote in between, so the lock
+        // is taken up front, like `begin immediate` would.
+        sqlx::query("update todos set id = id where false")
+            .execute(&mut tx)
+            .await?;
+
+        Ok(Box::new(SqliteReScheduling { tx }))
+    }
+
+    async fn delete_done_one_time_todos(
+        &self,
+        retention_days: i64,
+        today: NaiveDate,
+    ) -> Result<Vec<Todo>, LeditError> {
+        sqlx::query_as(
+            r#"
+                delete from 
+                    todos 
+                where 
+                    done_by is not null 
+                    and interval_days is null 
+                    and date(scheduled_for, '+' || ? || ' days') <= ?
+                returning *
+            "#,
+        )
+        .bind(retention_days)
+        .bind(today)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_board_message_id(&self, chat_id: i64) -> Result<Option<i32>, LeditError> {
+        let message_id =
+            sqlx::query_as::<_, (i32,)>("select message_id from boards where chat_id = ?")
+                .bind(chat_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .map(|(message_id,)| message_id);
+
+        Ok(message_id)
+    }
+
+    async fn set_board_message_id(&self, chat_id: i64, message_id: i32) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into boards (chat_id, message_id)
+                values ( ?, ? )
+                on conflict (chat_id) do update set message_id = excluded.message_id
+            "#,
+        )
+        .bind(chat_id)
+        .bind(message_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_board(&self, chat_id: i64) -> Result<(), LeditError> {
+        sqlx::query("delete from boards where chat_id = ?")
+            .bind(chat_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_stale_board_chat_ids(&self, today: NaiveDate) -> Result<Vec<i64>, LeditError> {
+        let chat_ids = sqlx::query_as::<_, (i64,)>(
+            r#"
+                select chat_id 
+                from boards 
+                where refreshed_on is null or refreshed_on < ?
+            "#,
+        )
+        .bind(today)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|(chat_id,)| chat_id)
+        .collect();
+
+        Ok(chat_ids)
+    }
+
+    async fn set_board_refreshed_on(
+        &self,
+        chat_id: i64,
+        today: NaiveDate,
+    ) -> Result<(), LeditError> {
+        sqlx::query("update boards set refreshed_on = ? where chat_id = ?")
+            .bind(today)
+            .bind(chat_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn insert_undo_entry(&self, entry: &UndoEntry) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into undo_journal (id, chat_id, "action", todo, recorded_at)
+                values ( ?, ?, ?, ?, ? )
+            "#,
+        )
+        .bind(entry.id)
+        .bind(entry.chat_id)
+        .bind(&entry.action)
+        .bind(Json(&entry.todo))
+        .bind(entry.recorded_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn take_last_undo_entry(
+        &self,
+        chat_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Option<UndoEntry>, LeditError> {
+        let entry: Option<(Uuid, i64, String, Json<Todo>, DateTime<Utc>)> = sqlx::query_as(
+            r#"
+                delete from 
+                    undo_journal 
+                where 
+                    id = (
+                        select id from undo_journal 
+                        where chat_id = ? and recorded_at >= ? 
+                        order by recorded_at desc 
+                        limit 1
+                    )
+                returning id, chat_id, "action", todo, recorded_at
+            "#,
+        )
+        .bind(chat_id)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(
+            entry.map(|(id, chat_id, action, todo, recorded_at)| UndoEntry {
+                id,
+                chat_id,
+                action,
+                todo: todo.0,
+                recorded_at,
+            }),
+        )
+    }
+
+    async fn delete_undo_entries_before(&self, before: DateTime<Utc>) -> Result<(), LeditError> {
+        sqlx::query("delete from undo_journal where recorded_at < ?")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn lock_job(&self, name: &str) -> Result<Option<Box<dyn JobLock>>, LeditError> {
+        Ok(Some(Box::new(SqliteJobLock {
+            pool: self.pool.clone(),
+            name: name.to_string(),
+        })))
+    }
+}
+
+/// A sqlite database is used by a single instance only, so there is nothing to lock.
+struct SqliteJobLock {
+    pool: Pool<Sqlite>,
+    name: String,
+}
+
+#[async_trait]
+impl JobLock for SqliteJobLock {
+    async fn last_run_at(&mut self) -> Result<Option<DateTime<Utc>>, LeditError> {
+        let last_run_at = sqlx::query_as::<_, (DateTime<Utc>,)>(
+            "select last_run_at from job_runs where name = ?",
+        )
+        .bind(&self.name)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|(last_run_at,)| last_run_at);
+
+        Ok(last_run_at)
+    }
+
+    async fn record_run(
+        self: Box<Self>,
+        run_at: DateTime<Utc>,
+        error: Option<String>,
+    ) -> Result<(), LeditError> {
+        sqlx::query(
+            r#"
+                insert into job_runs (name, last_run_at, last_success_at, last_error)
+                values ( ?, ?, ?, ? )
+                on conflict (name) do update set
+                    last_run_at = excluded.last_run_at,
+                    last_success_at = coalesce(excluded.last_success_at, job_runs.last_success_at),
+                    last_error = excluded.last_error
+            "#,
+        )
+        .bind(&self.name)
+        .bind(run_at)
+        .bind(error.is_none().then_some(run_at))
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn release(self: Box<Self>) -> Result<(), LeditError> {
+        Ok(())
+    }
+}
+
+/// Holds the write lock from the start, so writes of other connections wait for the
+/// transaction and the todos to re-schedule can't change in between.
+struct SqliteReScheduling {
+    tx: Transaction<'static, Sqlite>,
+}
+
+#[async_trait]
+impl ReScheduling for SqliteReScheduling {
+    async fn get_todos(&mut self, today: NaiveDate) -> Result<Vec<Todo>, LeditError> {
+        sqlx::query_as(
+            r#"
+                select *
+                from todos
+                where 
+                    interval_days is not null
+                    and done_by is not null
+                    and date(scheduled_for, '+' || interval_days || ' days') <= ?
+            "#,
+        )
+        .bind(today)
+        .fetch_all(&mut self.tx)
+        .await
+        .map_err(|err| err.into())
+    }
+
+    async fn get_chat_member_ids(
+        &mut self,
+        chat_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Uuid>>, LeditError> {
+        // sqlite has no arrays, the ids are passed as json array instead
+        let members = sqlx::query_as::<_, (i64, Uuid)>(
+            "select chat_id, id from chat_members where chat_id in (select value from json_each(?))",
+        )
+        .bind(Json(chat_ids))
+        .fetch_all(&mut self.tx)
+        .await?
+        .into_iter()
+        .into_group_map();
+
+        Ok(members)
+    }
+
+    async fn get_assign_creator(
+        &mut self,
+        chat_ids: &[i64],
+    ) -> Result<HashMap<i64, bool>, LeditError> {
+        let assign_creator = sqlx::query_as::<_, (i64, bool)>(
+            r#"
+                select chat_id, assign_creator 
+                from chat_settings 
+                where chat_id in (select value from json_each(?))
+            "#,
+        )
+        .bind(Json(chat_ids))
+        .fetch_all(&mut self.tx)
+        .await?
+        .into_iter()
+        .collect();
+
+        Ok(assign_creator)
+    }
+
+    async fn commit(
+        mut self: Box<Self>,
+        assignments: &[(Uuid, Uuid)],
+        today: NaiveDate,
+    ) -> Result<(), LeditError> {
+        for (todo_id, assigned_user) in assignments {
+            sqlx::query(
+                r#"
+                    update todos
+                    set
+                        done_by = null,
+                        scheduled_for = ?,
+                        assigned_user = ?
+                    where 
+                        id = ?
+                "#,
+            )
+            .bind(today)
+            .bind(assigned_user)
+            .bind(todo_id)
+            .execute(&mut self.tx)
+            .await?;
+        }
+
+        self.tx.commit().await?;
+
+        Ok(())
+    }
+}
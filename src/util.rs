@@ -1,22 +1,46 @@
-use chrono::{Datelike, NaiveDate, Utc};
+use crate::{
+    config::Config,
+    error::LeditError,
+    storage::{self, Storage},
+};
 use frankenstein::AsyncApi;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
-use std::env;
 
-pub async fn get_pool_and_api() -> (Pool<Postgres>, AsyncApi) {
-    let token = env::var("TOKEN").expect("missing TOKEN env var");
-    let api = AsyncApi::new(&token);
-    let database_url = env::var("DATABASE_URL").expect("missing DATABASE_URL env var");
-    let pool = PgPoolOptions::new()
-        .max_connections(16)
-        .connect(&database_url)
-        .await
-        .expect("failed to get connection pool");
+pub async fn get_storage_and_api(
+    config: &Config,
+) -> Result<(Box<dyn Storage>, AsyncApi), LeditError> {
+    let api = AsyncApi::new(&config.token);
+    let storage = storage::connect(config).await?;
 
-    (pool, api)
+    Ok((storage, api))
 }
 
-pub fn today() -> NaiveDate {
-    let today = Utc::now();
-    NaiveDate::from_ymd_opt(today.year(), today.month(), today.day()).expect("invalid date")
+/// Resolves once the process receives SIGINT or (on unix) SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!("failed to listen for ctrl-c, err: {}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                tracing::error!("failed to listen for SIGTERM, err: {}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
 }
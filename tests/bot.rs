@@ -0,0 +1,1028 @@
+mod common;
+
+use chrono::{Duration as ChronoDuration, NaiveTime};
+use common::{
+    callback_query, context, group_message, memory_storage, message, private_message, test_config,
+    TestBot, CHAT_ID,
+};
+use frankenstein::{
+    objects::{UpdateBuilder, UserBuilder},
+    ChatId, Message, ReplyMarkup,
+};
+use ledit::{
+    delete_one_time_todos, handle_callback_query, handle_message, listen_for_updates,
+    re_schedule_todos, register_commands, run_job_if_due, send_digests, send_reminders,
+    testing::{display_name, RecordingMessenger},
+    Clock, Context, Job, Schedule, Storage,
+};
+use std::time::Duration;
+use tokio::sync::watch;
+
+#[tokio::test]
+async fn replies_to_help_and_start() {
+    let messenger = RecordingMessenger::default();
+    let (storage, config) = (memory_storage().await, test_config());
+
+    let ctx = context(&storage, &config, &messenger);
+
+    handle_message(&message("/help"), ctx).await;
+    handle_message(&message("/start"), ctx).await;
+
+    let sent = messenger.sent_messages();
+    assert_eq!(sent.len(), 2);
+    assert!(sent.iter().all(|v| v.chat_id == ChatId::Integer(CHAT_ID)));
+    assert!(sent[0]
+        .text
+        .contains("/add every 2 days: &lt;your todo&gt;"));
+    assert!(sent[1].text.starts_with("Hi there 👋"));
+    assert!(sent[1].text.contains("I'm ledit bot."));
+}
+
+#[tokio::test]
+async fn greets_with_the_texts_of_the_deployment() {
+    let messenger = RecordingMessenger::default();
+    let storage = memory_storage().await;
+    let mut config = test_config();
+    config.bot_name = "Putzplan".to_string();
+    config.text_overrides.insert(
+        "start.txt".to_string(),
+        "Welcome to {chat}, I'm {bot}.".to_string(),
+    );
+    let ctx = context(&storage, &config, &messenger);
+
+    handle_message(&group_message(-1, "Flat", 1, "alice", "/start"), ctx).await;
+    handle_message(&group_message(-2, "WG", 1, "alice", "/language de"), ctx).await;
+    handle_message(&group_message(-2, "WG", 1, "alice", "/start"), ctx).await;
+
+    let sent = messenger.take_texts();
+    assert_eq!(sent[0], "Welcome to Flat, I'm Putzplan.");
+    // languages without an override keep the embedded text
+    assert!(sent[2].starts_with("Hallo 👋\n\nIch bin Putzplan."));
+}
+
+#[tokio::test]
+async fn registers_the_command_menu() {
+    let messenger = RecordingMessenger::default();
+    register_commands(&messenger).await.unwrap();
+
+    let menus = messenger.command_menus();
+    assert_eq!(menus.len(), 2);
+    assert_eq!(menus[0].language_code, None);
+    assert_eq!(menus[0].commands[0].command, "help");
+    assert!(menus[0].commands.iter().all(|v| v.command != "start"));
+    assert_eq!(menus[1].language_code.as_deref(), Some("de"));
+    assert!(menus[1].commands.iter().any(|v| v.command == "loeschen"));
+}
+
+#[tokio::test]
+async fn accepts_commands_addressed_to_the_bot() {
+    for bot in TestBot::all().await {
+        assert_eq!(
+            bot.send(1, "alice", "/add@ledit_bot Clean kitchen").await,
+            vec!["Added: Clean kitchen"]
+        );
+        assert!(bot.send(1, "alice", "/TODOS@ledit_bot").await[0].contains("Clean kitchen"));
+        assert!(bot.send(1, "alice", "/todosx").await.is_empty());
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn ignores_unknown_messages() {
+    let messenger = RecordingMessenger::default();
+    let (storage, config) = (memory_storage().await, test_config());
+
+    handle_message(
+        &message("hello there"),
+        context(&storage, &config, &messenger),
+    )
+    .await;
+
+    assert!(messenger.sent_messages().is_empty());
+}
+
+#[tokio::test]
+async fn listens_for_updates_until_shutdown() {
+    let messenger = RecordingMessenger::default();
+    messenger.push_update(
+        UpdateBuilder::default()
+            .update_id(1u32)
+            .message(message("/help"))
+            .build()
+            .unwrap(),
+    );
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (storage, config) = (memory_storage().await, test_config());
+    let listen = listen_for_updates(context(&storage, &config, &messenger), shutdown_rx);
+    let shutdown = async {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        shutdown_tx.send(true).unwrap();
+    };
+    let (result, _) = tokio::join!(listen, shutdown);
+
+    assert!(result.is_ok());
+    assert_eq!(messenger.take_texts().len(), 1);
+}
+
+#[tokio::test]
+async fn add_list_and_delete_todos() {
+    for bot in TestBot::all().await {
+        assert_eq!(
+            bot.send(1, "alice", "/add Clean kitchen").await,
+            vec!["Added: Clean kitchen"]
+        );
+        assert_eq!(
+            bot.send(1, "alice", "/add every 7 days: Clean mirrors")
+                .await,
+            vec!["Added: Clean mirrors"]
+        );
+
+        let list = bot.send(1, "alice", "/todos").await;
+        assert_eq!(list.len(), 1);
+        assert!(list[0].starts_with(
+            "List of all todos:\n\n☑️ 1. Clean kitchen  \n☑️ 2. Clean mirrors (🔄 7 days) "
+        ));
+        assert!(list[0].contains(
+            "Todos for <a href=\"tg://user?id=1\">alice</a>:\n\n☑️ Clean kitchen\n☑️ Clean mirrors"
+        ));
+
+        let deleted = bot.send(1, "alice", "/delete 1").await;
+        assert!(deleted[0]
+            .starts_with("Deleted: Clean kitchen\n\nList of all todos:\n\n☑️ 1. Clean mirrors"));
+        assert_eq!(
+            bot.send(1, "alice", "/delete 5").await,
+            vec!["Todo not found."]
+        );
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn check_toggles_todo() {
+    for bot in TestBot::all().await {
+        bot.send(1, "alice", "/add Clean kitchen").await;
+
+        assert_eq!(
+            bot.send(2, "bob", "/check 1").await,
+            vec!["✅ Clean kitchen"]
+        );
+        assert!(bot.send(1, "alice", "/todos").await[0].contains("✅ 1. <s>Clean kitchen</s>"));
+        assert_eq!(
+            bot.send(2, "bob", "/check 1").await,
+            vec!["☑️ Clean kitchen"]
+        );
+        assert_eq!(
+            bot.send(2, "bob", "/check 2").await,
+            vec!["Todo not found."]
+        );
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn refreshes_names_of_chat_members() {
+    for bot in TestBot::all().await {
+        bot.send(1, "alice", "/add Clean kitchen").await;
+        assert!(bot.send(1, "alice", "/todos").await[0]
+            .contains("Todos for <a href=\"tg://user?id=1\">alice</a>:"));
+
+        let list = bot.send(1, "Alicia", "/todos").await;
+        assert!(list[0].contains(r#"Todos for <a href="tg://user?id=1">Alicia</a>:"#));
+        assert!(!list[0].contains("alice"));
+
+        bot.cleanup().await;
+    }
+}
+
+#[test]
+fn prefers_username_for_display_name() {
+    let user = |username: Option<&str>, last_name: Option<&str>| {
+        let mut builder = UserBuilder::default();
+        builder.id(7u64).is_bot(false).first_name("Alice");
+        if let Some(v) = username {
+            builder.username(v);
+        }
+        if let Some(v) = last_name {
+            builder.last_name(v);
+        }
+        builder.build().unwrap()
+    };
+
+    assert_eq!(display_name(&user(Some("ally"), Some("Smith"))), "ally");
+    assert_eq!(display_name(&user(Some(" "), Some("Smith"))), "Alice Smith");
+    assert_eq!(display_name(&user(None, None)), "Alice");
+}
+
+#[tokio::test]
+async fn escapes_todos_in_html_messages() {
+    for bot in TestBot::all().await {
+        handle_message(&message("/add Fix <b> & co"), bot.ctx()).await;
+        handle_message(&message("/todos"), bot.ctx()).await;
+
+        let sent = bot.messenger.sent_messages();
+        assert!(sent.iter().all(|v| v.parse_mode.as_deref() == Some("HTML")));
+        assert_eq!(sent[0].text, "Added: Fix &lt;b&gt; &amp; co");
+        assert!(sent[1].text.contains("☑️ 1. Fix &lt;b&gt; &amp; co"));
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn splits_long_lists_into_several_messages() {
+    for bot in TestBot::all().await {
+        for n in 0..100 {
+            bot.send(
+                1,
+                "alice",
+                &format!(
+                    "/add every {} days: Todo {} with a long enough description",
+                    n + 1,
+                    n
+                ),
+            )
+            .await;
+        }
+
+        let list = bot.send(1, "alice", "/todos").await;
+        assert!(list.len() > 1);
+        assert!(list.iter().all(|v| v.encode_utf16().count() <= 4096));
+        assert!(list[0].starts_with("List of all todos:"));
+        assert!(list
+            .last()
+            .unwrap()
+            .ends_with("Todo 99 with a long enough description"));
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn marks_overdue_todos() {
+    for bot in TestBot::all().await {
+        bot.send(1, "alice", "/add Clean kitchen").await;
+        assert!(!bot.send(1, "alice", "/todos").await[0].contains('⏳'));
+
+        bot.travel_days(2);
+        assert!(bot.send(1, "alice", "/todos").await[0].contains("☑️⏳ <b>Clean kitchen</b>"));
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn re_schedules_done_recurring_todos() {
+    for bot in TestBot::all().await {
+        bot.send(1, "alice", "/add every 2 days: Clean mirrors")
+            .await;
+        bot.send(1, "alice", "/check 1").await;
+
+        // not due yet
+        bot.travel_days(1);
+        re_schedule_todos(bot.now(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        assert!(bot.send(1, "alice", "/todos").await[0].contains("🗓 1. <s>Clean mirrors</s>"));
+
+        bot.travel_days(1);
+        re_schedule_todos(bot.now(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        let list = bot.send(1, "alice", "/todos").await;
+        assert!(list[0].contains("☑️ 1. Clean mirrors (🔄 2 days)"));
+        assert!(
+            list[0].contains("Todos for <a href=\"tg://user?id=1\">alice</a>:\n\n☑️ Clean mirrors")
+        );
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn deletes_old_done_one_time_todos() {
+    for bot in TestBot::all().await {
+        bot.send(1, "alice", "/add Take out trash").await;
+        bot.send(1, "alice", "/add Buy milk").await;
+        bot.send(1, "alice", "/check 2").await;
+
+        delete_one_time_todos(1, bot.today(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        assert_eq!(bot.messenger.take_texts(), Vec::<String>::new());
+
+        bot.travel_days(1);
+        delete_one_time_todos(1, bot.today(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        assert_eq!(
+            bot.messenger.take_texts(),
+            vec!["🗑 Deleting old & done todo: Take out trash"]
+        );
+        let list = bot.send(1, "alice", "/todos").await;
+        assert!(list[0].starts_with("List of all todos:\n\n☑️ 1. <b>Buy milk</b>"));
+        assert!(!list[0].contains("Take out trash"));
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn runs_daily_jobs_once_per_day() {
+    for bot in TestBot::all().await {
+        let reminders_at_nine = Schedule::DailyAt(NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+
+        bot.send(1, "alice", "/add Clean kitchen").await;
+        bot.travel_days(1);
+
+        run_job_if_due(Job::Reminders, reminders_at_nine, bot.ctx())
+            .await
+            .unwrap();
+        assert_eq!(
+            bot.messenger.take_texts(),
+            vec!["Reminder, these todos are overdue:\n\n☑️⏳ <b>Clean kitchen</b> (<a href=\"tg://user?id=1\">alice</a>)"]
+        );
+
+        // already ran today
+        run_job_if_due(Job::Reminders, reminders_at_nine, bot.ctx())
+            .await
+            .unwrap();
+        assert!(bot.messenger.take_texts().is_empty());
+
+        // a missed day is caught up with a single run
+        bot.travel_days(2);
+        run_job_if_due(Job::Reminders, reminders_at_nine, bot.ctx())
+            .await
+            .unwrap();
+        assert_eq!(bot.messenger.take_texts().len(), 1);
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn runs_each_job_on_one_instance_only() {
+    for bot in TestBot::all().await {
+        // instances only share their job locks on postgres
+        let other = match bot.other_instance().await {
+            Some(other) => other,
+            None => continue,
+        };
+        let other_ctx = Context {
+            storage: &other,
+            ..bot.ctx()
+        };
+        let reminders_at_nine = Schedule::DailyAt(NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+
+        bot.send(1, "alice", "/add Clean kitchen").await;
+        bot.travel_days(1);
+
+        let lock = bot
+            .storage
+            .lock_job(Job::Reminders.name())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(other
+            .lock_job(Job::Reminders.name())
+            .await
+            .unwrap()
+            .is_none());
+        run_job_if_due(Job::Reminders, reminders_at_nine, other_ctx)
+            .await
+            .unwrap();
+        assert!(bot.messenger.take_texts().is_empty());
+
+        // once recorded, the run counts for every instance
+        lock.record_run(bot.clock.now(), None).await.unwrap();
+        run_job_if_due(Job::Reminders, reminders_at_nine, other_ctx)
+            .await
+            .unwrap();
+        assert!(bot.messenger.take_texts().is_empty());
+
+        bot.travel_days(1);
+        run_job_if_due(Job::Reminders, reminders_at_nine, other_ctx)
+            .await
+            .unwrap();
+        assert_eq!(bot.messenger.take_texts().len(), 1);
+
+        other.close().await;
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn keeps_sending_to_other_chats_when_one_removed_the_bot() {
+    for bot in TestBot::all().await {
+        bot.receive(&group_message(-1, "Flat", 1, "alice", "/add Clean kitchen"))
+            .await;
+        bot.receive(&group_message(
+            -2,
+            "Family",
+            1,
+            "alice",
+            "/add Call grandma",
+        ))
+        .await;
+        bot.travel_days(1);
+        bot.messenger.block_by(-1);
+
+        send_reminders(bot.today(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        let reminders = bot.messenger.take_texts();
+        assert_eq!(reminders.len(), 1);
+        assert!(reminders[0].contains("Call grandma"));
+
+        send_digests(bot.today(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        let digests = bot.messenger.take_texts();
+        assert_eq!(digests.len(), 1);
+        assert!(digests[0].contains("Call grandma"));
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn pins_a_board_that_updates_itself() {
+    for bot in TestBot::all().await {
+        bot.send(1, "alice", "/add Take out trash").await;
+
+        let board = bot.send(1, "alice", "/board").await;
+        assert_eq!(board.len(), 1);
+        assert!(board[0].starts_with("📌 Todo board"));
+        assert!(board[0].contains("☑️ 1. Take out trash"));
+
+        let pins = bot.messenger.pinned_messages();
+        assert_eq!(pins.len(), 1);
+        let board_id = pins[0].message_id;
+        assert_eq!(
+            bot.storage.get_board_message_id(CHAT_ID).await.unwrap(),
+            Some(board_id)
+        );
+
+        bot.send(1, "alice", "/add Buy milk").await;
+        bot.send(1, "alice", "/check 1").await;
+        let board = bot.messenger.message_text(CHAT_ID, board_id).unwrap();
+        assert!(board.contains("✅ 1. <s>Buy milk</s>"));
+        assert!(board.contains("☑️ 2. Take out trash"));
+        assert_eq!(bot.messenger.take_edits().len(), 2);
+
+        // the day changes the board even if no todo needs to be re-scheduled
+        bot.travel_days(1);
+        re_schedule_todos(bot.now(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        assert_eq!(bot.messenger.take_edits().len(), 1);
+
+        // later runs of the same day leave it alone
+        re_schedule_todos(bot.now(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        assert!(bot.messenger.take_edits().is_empty());
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn forgets_a_deleted_board() {
+    for bot in TestBot::all().await {
+        bot.send(1, "alice", "/board").await;
+        let board_id = bot.messenger.pinned_messages()[0].message_id;
+
+        bot.messenger.delete_message(CHAT_ID, board_id);
+        let reply = bot.send(1, "alice", "/add Take out trash").await;
+        assert_eq!(reply.len(), 1);
+        assert!(bot.messenger.take_edits().is_empty());
+        assert_eq!(
+            bot.storage.get_board_message_id(CHAT_ID).await.unwrap(),
+            None
+        );
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn lists_my_todos_of_all_groups_in_private_chat() {
+    for bot in TestBot::all().await {
+        bot.receive(&group_message(-1, "Flat", 1, "alice", "/add Clean kitchen"))
+            .await;
+        bot.receive(&group_message(
+            -2,
+            "Family",
+            1,
+            "alice",
+            "/add Call grandma",
+        ))
+        .await;
+        bot.receive(&group_message(-3, "Sports", 2, "bob", "/add Pump balls"))
+            .await;
+
+        let reply = bot.receive(&message("/mytodos")).await;
+        assert_eq!(
+            reply,
+            vec!["Send /mytodos to me in a private chat to see your todos of all groups."]
+        );
+
+        handle_message(&private_message(1, "alice", "/mytodos"), bot.ctx()).await;
+        let list = bot.messenger.sent_messages().pop().unwrap();
+        assert_eq!(
+            list.text,
+            "Your todos:\n\n<b>Family</b>\n☑️ Call grandma\n\n<b>Flat</b>\n☑️ Clean kitchen"
+        );
+        let buttons = match list.reply_markup {
+            Some(ReplyMarkup::InlineKeyboardMarkup(v)) => v.inline_keyboard,
+            _ => panic!("missing buttons"),
+        };
+        assert_eq!(buttons[1][0].text, "☑️ Clean kitchen (Flat)");
+        let check_data = buttons[1][0].callback_data.clone().unwrap();
+        let list_id = bot.messenger.last_message_id();
+        let mut list_message = private_message(1, "alice", "");
+        list_message.message_id = list_id;
+        bot.messenger.take_texts();
+
+        // bob is no member of the flat
+        let press = callback_query(2, "bob", &check_data, list_message.clone());
+        handle_callback_query(&press, bot.ctx()).await;
+        assert_eq!(
+            bot.messenger.take_answers()[0].text.as_deref(),
+            Some("Todo not found.")
+        );
+
+        let press = callback_query(1, "alice", &check_data, list_message);
+        handle_callback_query(&press, bot.ctx()).await;
+        assert_eq!(
+            bot.messenger.take_answers()[0].text.as_deref(),
+            Some("✅ Done: Clean kitchen")
+        );
+        assert_eq!(
+            bot.messenger.message_text(1, list_id).unwrap(),
+            "Your todos:\n\n<b>Family</b>\n☑️ Call grandma\n\n<b>Flat</b>\n✅ <s>Clean kitchen</s>"
+        );
+        let flat = bot
+            .receive(&group_message(-1, "Flat", 1, "alice", "/todos"))
+            .await;
+        assert!(flat[0].contains("✅ 1. <s>Clean kitchen</s>"));
+
+        bot.cleanup().await;
+    }
+}
+
+fn flat(user_id: u64, first_name: &str, text: &str) -> Message {
+    group_message(-1, "Flat", user_id, first_name, text)
+}
+
+#[tokio::test]
+async fn notifies_members_who_turned_notifications_on() {
+    for bot in TestBot::all().await {
+        assert_eq!(
+            bot.receive(&flat(2, "bob", "/notify on")).await,
+            vec!["Send /notify on to me in a private chat, I can only message you after you started a chat with me."]
+        );
+        for (user_id, first_name) in [(1, "alice"), (2, "bob")] {
+            let reply = bot
+                .receive(&private_message(user_id, first_name, "/notify on"))
+                .await;
+            assert!(reply[0].starts_with("🔔 Notifications are on."));
+        }
+        assert!(bot.receive(&private_message(2, "bob", "/notify")).await[0]
+            .starts_with("🔔 Notifications are on."));
+
+        // new todos, the sender is not notified about todos they got themselves
+        bot.receive(&flat(2, "bob", "hi")).await;
+        for n in 0..8 {
+            handle_message(&flat(1, "alice", &format!("/add Todo {}", n)), bot.ctx()).await;
+        }
+        let bob = bot.storage.get_chat_member(2, -1).await.unwrap().unwrap();
+        let todos_of_bob = bot
+            .storage
+            .get_sorted_todos(-1)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|v| v.assigned_user == bob.id)
+            .map(|v| format!("🆕 New todo for you in <b>Flat</b>: {}", v.description))
+            .collect::<Vec<String>>();
+        let sent = bot.messenger.take_messages();
+        assert!(!sent.iter().any(|v| v.chat_id == ChatId::Integer(1)));
+        let notices = sent
+            .into_iter()
+            .filter(|v| v.chat_id == ChatId::Integer(2))
+            .map(|v| v.text)
+            .collect::<Vec<String>>();
+        assert_eq!(notices, todos_of_bob);
+
+        // re-scheduled and overdue todos, without creators alice gets the todos of bob
+        bot.receive(&flat(2, "bob", "/assigncreator off")).await;
+        handle_message(&flat(2, "bob", "/add every 1 day: Water plants"), bot.ctx()).await;
+        bot.receive(&flat(2, "bob", "/check 9")).await;
+        bot.messenger.take_messages();
+        bot.travel_days(1);
+        re_schedule_todos(bot.now(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        assert_eq!(
+            bot.messenger.take_texts(),
+            vec!["🔄 Your turn in <b>Flat</b>: Water plants"]
+        );
+        send_reminders(bot.today(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        let overdue_of_bob = bot
+            .messenger
+            .take_messages()
+            .into_iter()
+            .filter(|v| v.chat_id == ChatId::Integer(2))
+            .map(|v| v.text)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            overdue_of_bob,
+            todos_of_bob
+                .iter()
+                .map(|v| v.replace(
+                    "🆕 New todo for you in <b>Flat</b>: ",
+                    "⏳ Overdue in <b>Flat</b>: <b>"
+                ) + "</b>")
+                .collect::<Vec<String>>()
+        );
+
+        for (user_id, first_name) in [(1, "alice"), (2, "bob")] {
+            bot.receive(&private_message(user_id, first_name, "/notify off"))
+                .await;
+        }
+        send_reminders(bot.today(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        assert_eq!(bot.messenger.take_messages().len(), 1);
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn turns_notifications_off_when_blocked() {
+    for bot in TestBot::all().await {
+        bot.receive(&private_message(1, "alice", "/notify on"))
+            .await;
+        bot.send(1, "alice", "/add Take out trash").await;
+        bot.messenger.block_by(1);
+
+        bot.travel_days(1);
+        send_reminders(bot.today(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+
+        // the reminder in the group is sent anyway
+        assert_eq!(bot.messenger.take_texts().len(), 1);
+        assert!(!bot.storage.get_notifications(1).await.unwrap());
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn speaks_the_language_of_the_chat() {
+    for bot in TestBot::all().await {
+        assert_eq!(
+            bot.send(1, "alice", "/sprache").await,
+            vec!["🌍 This chat speaks English. Available languages: en, de"]
+        );
+        assert_eq!(
+            bot.send(1, "alice", "/language de").await,
+            vec!["🌍 Dieser Chat spricht jetzt Deutsch."]
+        );
+
+        assert_eq!(
+            bot.send(1, "alice", "/neu alle 2 Tage: Öl wechseln").await,
+            vec!["Hinzugefügt: Öl wechseln"]
+        );
+        bot.send(1, "alice", "/erledigt 1").await;
+        bot.travel_days(1);
+        let list = bot.send(1, "alice", "/aufgaben").await;
+        assert!(list[0].starts_with("Alle Aufgaben:"));
+        assert!(list[0].contains("🗓 1. <s>Öl wechseln</s> (🔄 2 Tage, wieder am 10.02.)"));
+        assert!(bot.send(1, "alice", "/hilfe").await[0].starts_with("/hilfe - diese Nachricht"));
+
+        // other chats keep speaking english
+        let reply = bot
+            .receive(&group_message(
+                -1,
+                "Flat",
+                1,
+                "alice",
+                "/add Take out trash",
+            ))
+            .await;
+        assert_eq!(reply, vec!["Added: Take out trash"]);
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn restricts_operations_to_admins() {
+    for bot in TestBot::all().await {
+        bot.messenger.set_admins(CHAT_ID, &[1]);
+        bot.send(1, "alice", "/add Clean kitchen").await;
+        bot.send(2, "bob", "/add Buy milk").await;
+
+        assert_eq!(
+            bot.send(2, "bob", "/permissions delete admins").await,
+            vec!["🔐 From now on admins can delete todos."]
+        );
+        assert_eq!(
+            bot.send(2, "bob", "/delete 1").await,
+            vec!["⛔ Only admins of this chat can delete todos."]
+        );
+        assert_eq!(bot.send(2, "bob", "/todos").await.len(), 1);
+        assert!(bot.send(1, "alice", "/delete 1").await[0].starts_with("Deleted: "));
+
+        bot.send(1, "alice", "/permissions settings admins").await;
+        assert_eq!(
+            bot.send(2, "bob", "/language de").await,
+            vec!["⛔ Only admins of this chat can change the settings."]
+        );
+        assert_eq!(
+            bot.send(2, "bob", "/permissions").await,
+            vec!["🔐 Permissions of this chat:\n\ndelete todos: <b>admins</b>\nchange the settings: <b>admins</b>\n\nChange them with /permissions delete admins, /permissions delete creator or /permissions settings everyone."]
+        );
+
+        // private chats belong to their user alone
+        bot.storage
+            .set_chat_permission(2, "settings", "admins")
+            .await
+            .unwrap();
+        assert_eq!(
+            bot.receive(&private_message(2, "bob", "/language de"))
+                .await,
+            vec!["🌍 Dieser Chat spricht jetzt Deutsch."]
+        );
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn records_who_created_a_todo() {
+    for bot in TestBot::all().await {
+        bot.send(1, "alice", "/add every 3 days: Clean kitchen")
+            .await;
+        bot.send(2, "bob", "/add Buy milk").await;
+
+        let details = bot.send(2, "bob", "/show 2").await;
+        assert!(details[0].starts_with("<b>Clean kitchen</b>\n🔄 3 days\n👤 Assigned to "));
+        assert!(details[0].ends_with("☑️ Open\n✍️ Added by alice on Feb 8"));
+        assert_eq!(bot.send(2, "bob", "/show 3").await, vec!["Todo not found."]);
+
+        // only the creator and admins may delete
+        bot.send(1, "alice", "/permissions delete creator").await;
+        assert_eq!(
+            bot.send(2, "bob", "/delete 2").await,
+            vec!["⛔ Only whoever added the todo and admins of this chat can delete todos."]
+        );
+        assert!(bot.send(2, "bob", "/delete 1").await[0].starts_with("Deleted: Buy milk"));
+        bot.messenger.set_admins(CHAT_ID, &[2]);
+        let question = bot.ask(2, "bob", "/delete 1").await;
+        assert_eq!(
+            bot.press(2, "bob", &question, "Yes, delete").await,
+            "Deleted: Clean kitchen"
+        );
+        assert!(bot.send(2, "bob", "/permissions settings creator").await[0]
+            .starts_with("🔐 Permissions of this chat:"));
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn assigns_todos_to_others_than_their_creator() {
+    for bot in TestBot::all().await {
+        bot.send(2, "bob", "/todos").await;
+        assert!(bot.send(1, "alice", "/assigncreator off").await[0]
+            .starts_with("🎲 Todos are not assigned to whoever added them"));
+
+        for n in 0..10 {
+            bot.send(1, "alice", &format!("/add every 1 day: Todo {}", n))
+                .await;
+            bot.send(1, "alice", &format!("/check {}", n + 1)).await;
+        }
+        bot.travel_days(1);
+        re_schedule_todos(bot.now(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+
+        let bob = bot
+            .storage
+            .get_chat_member(2, CHAT_ID)
+            .await
+            .unwrap()
+            .unwrap();
+        let todos = bot.storage.get_sorted_todos(CHAT_ID).await.unwrap();
+        assert!(todos.iter().all(|v| v.assigned_user == bob.id));
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn undoes_deleting_and_checking_todos() {
+    for bot in TestBot::all().await {
+        assert_eq!(
+            bot.send(1, "alice", "/undo").await,
+            vec!["Nothing to undo. Deleting, checking and re-assigning todos can be undone for 10 minutes."]
+        );
+
+        bot.send(1, "alice", "/add every 3 days: Clean kitchen")
+            .await;
+        bot.send(1, "alice", "/check 1").await;
+        bot.travel_days(3);
+        re_schedule_todos(bot.now(), bot.storage.as_ref(), &bot.messenger)
+            .await
+            .unwrap();
+        let todos = bot.storage.get_sorted_todos(CHAT_ID).await.unwrap();
+
+        let question = bot.ask(1, "alice", "/delete 1").await;
+        bot.press(1, "alice", &question, "Yes, delete").await;
+        assert_eq!(
+            bot.send(2, "bob", "/undo").await,
+            vec!["↩️ Restored: Clean kitchen"]
+        );
+        // with its schedule and creator
+        assert_eq!(bot.storage.get_sorted_todos(CHAT_ID).await.unwrap(), todos);
+
+        bot.send(1, "alice", "/check 1").await;
+        assert_eq!(
+            bot.send(1, "alice", "/undo").await,
+            vec!["↩️ Undid checking: Clean kitchen"]
+        );
+        assert_eq!(bot.storage.get_sorted_todos(CHAT_ID).await.unwrap(), todos);
+        // re-scheduling keeps the todo open but gives it back to whoever had it
+        assert_eq!(
+            bot.send(1, "alice", "/undo").await,
+            vec!["↩️ Assigned back to the previous member: Clean kitchen"]
+        );
+        assert_eq!(bot.storage.get_sorted_todos(CHAT_ID).await.unwrap(), todos);
+        assert!(bot.send(1, "alice", "/undo").await[0].starts_with("Nothing to undo."));
+
+        // only for a few minutes
+        let question = bot.ask(1, "alice", "/delete 1").await;
+        bot.press(1, "alice", &question, "Yes, delete").await;
+        bot.clock.advance(ChronoDuration::minutes(11));
+        assert!(bot.send(1, "alice", "/undo").await[0].starts_with("Nothing to undo."));
+        assert!(bot
+            .storage
+            .get_sorted_todos(CHAT_ID)
+            .await
+            .unwrap()
+            .is_empty());
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn confirms_deleting_recurring_todos() {
+    for bot in TestBot::all().await {
+        bot.messenger.set_admins(CHAT_ID, &[1]);
+        bot.send(1, "alice", "/add every 3 days: Clean kitchen")
+            .await;
+        bot.send(1, "alice", "/add Buy milk").await;
+        assert!(bot.send(1, "alice", "/delete 1").await[0].starts_with("Deleted: Buy milk"));
+
+        let question = bot.ask(1, "alice", "/delete 1").await;
+        assert_eq!(
+            question.0.text,
+            "🗑 Delete <b>Clean kitchen</b> (🔄 3 days)?"
+        );
+        assert_eq!(
+            bot.press(1, "alice", &question, "No, keep it").await,
+            "Kept: Clean kitchen"
+        );
+        assert_eq!(
+            bot.messenger.message_text(CHAT_ID, question.1).unwrap(),
+            "Kept: Clean kitchen"
+        );
+
+        // pressing yes needs the permission to delete
+        bot.send(1, "alice", "/permissions delete admins").await;
+        let question = bot.ask(1, "alice", "/delete 1").await;
+        assert_eq!(
+            bot.press(2, "bob", &question, "Yes, delete").await,
+            "⛔ Only admins of this chat can delete todos."
+        );
+        assert_eq!(
+            bot.messenger.message_text(CHAT_ID, question.1).unwrap(),
+            question.0.text
+        );
+
+        bot.clock.advance(ChronoDuration::minutes(6));
+        assert_eq!(
+            bot.press(1, "alice", &question, "Yes, delete").await,
+            "⌛ This question expired, send /delete again."
+        );
+        assert_eq!(
+            bot.storage.get_sorted_todos(CHAT_ID).await.unwrap().len(),
+            1
+        );
+
+        let question = bot.ask(1, "alice", "/delete 1").await;
+        assert_eq!(
+            bot.press(1, "alice", &question, "Yes, delete").await,
+            "Deleted: Clean kitchen"
+        );
+        assert_eq!(
+            bot.messenger.message_text(CHAT_ID, question.1).unwrap(),
+            "Deleted: Clean kitchen\n\nNo todo found."
+        );
+        assert_eq!(
+            bot.press(1, "alice", &question, "Yes, delete").await,
+            "Todo not found."
+        );
+
+        // optionally for all todos
+        assert!(bot.send(1, "alice", "/confirmdeletes on").await[0]
+            .starts_with("🗑 Deleting any todo asks for confirmation."));
+        bot.send(1, "alice", "/add Buy milk").await;
+        assert_eq!(
+            bot.send(1, "alice", "/delete 1").await,
+            vec!["🗑 Delete <b>Buy milk</b>?"]
+        );
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn cuts_the_remaining_todos_after_confirmed_deletes() {
+    for bot in TestBot::all().await {
+        for n in 0..80 {
+            bot.send(
+                1,
+                "alice",
+                &format!(
+                    "/add every {} days: Todo {} with a long enough description",
+                    n + 1,
+                    n
+                ),
+            )
+            .await;
+        }
+
+        let question = bot.ask(1, "alice", "/delete 1").await;
+        assert_eq!(
+            bot.press(1, "alice", &question, "Yes, delete").await,
+            "Deleted: Todo 0 with a long enough description"
+        );
+
+        let text = bot.messenger.message_text(CHAT_ID, question.1).unwrap();
+        assert!(text.starts_with("Deleted: Todo 0 with a long enough description"));
+        assert!(text.ends_with('…'));
+        assert!(text.encode_utf16().count() <= 4096);
+
+        bot.cleanup().await;
+    }
+}
+
+#[tokio::test]
+async fn keeping_todos_needs_the_permission_to_delete_them() {
+    for bot in TestBot::all().await {
+        bot.messenger.set_admins(CHAT_ID, &[1]);
+        bot.send(2, "bob", "/todos").await;
+        bot.send(1, "alice", "/add every 3 days: Clean kitchen")
+            .await;
+        bot.send(1, "alice", "/permissions delete admins").await;
+
+        let question = bot.ask(1, "alice", "/delete 1").await;
+        assert_eq!(
+            bot.press(2, "bob", &question, "No, keep it").await,
+            "⛔ Only admins of this chat can delete todos."
+        );
+        assert_eq!(
+            bot.messenger.message_text(CHAT_ID, question.1).unwrap(),
+            question.0.text
+        );
+
+        bot.clock.advance(ChronoDuration::minutes(6));
+        assert_eq!(
+            bot.press(1, "alice", &question, "No, keep it").await,
+            "⌛ This question expired, send /delete again."
+        );
+        assert_eq!(
+            bot.messenger.message_text(CHAT_ID, question.1).unwrap(),
+            "⌛ This question expired, send /delete again."
+        );
+        assert_eq!(
+            bot.storage.get_sorted_todos(CHAT_ID).await.unwrap().len(),
+            1
+        );
+
+        bot.cleanup().await;
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use ledit::Config;
+use std::{collections::HashMap, env, fs, path::PathBuf};
+use uuid::Uuid;
+
+/// The env vars every config needs, plus `vars`.
+fn vars(vars: &[(&str, &str)]) -> HashMap<String, String> {
+    [("TOKEN", "token"), ("DATABASE_URL", "sqlite::memory:")]
+        .iter()
+        .chain(vars)
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Writes `content` to a config file, removed by the caller.
+fn config_file(content: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("ledit_{}.toml", Uuid::new_v4()));
+    fs::write(&path, content).unwrap();
+
+    path
+}
+
+fn load_error(vars: &HashMap<String, String>) -> String {
+    Config::from_vars(vars).unwrap_err().to_string()
+}
+
+#[test]
+fn loads_defaults() {
+    let config = Config::from_vars(&vars(&[])).unwrap();
+
+    assert_eq!(config.token, "token");
+    assert_eq!(config.interval_ms, 10000);
+    assert_eq!(config.pool_max_connections, 16);
+    assert_eq!(config.default_timezone, Tz::UTC);
+    assert_eq!(config.bot_name, "ledit bot");
+    assert_eq!(config.reminder_time, None);
+    assert!(!config.simulation_throwaway_database);
+
+    assert_eq!(
+        Config::from_vars(&HashMap::new()).unwrap_err().to_string(),
+        "Config Error: `TOKEN` is not set"
+    );
+}
+
+#[test]
+fn env_vars_take_precedence_over_the_file() {
+    let path = config_file(
+        r#"
+            token = "file token"
+            interval_ms = 5000
+            bot_name = "file bot"
+            reminder_time = "08:00"
+        "#,
+    );
+
+    let config = Config::from_vars(&vars(&[
+        ("LEDIT_CONFIG", path.to_str().unwrap()),
+        ("BOT_NAME", "env bot"),
+        ("DEFAULT_TIMEZONE", "Europe/Berlin"),
+    ]))
+    .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(config.token, "token");
+    assert_eq!(config.bot_name, "env bot");
+    assert_eq!(config.interval_ms, 5000);
+    assert_eq!(config.default_timezone, Tz::Europe__Berlin);
+    assert_eq!(config.reminder_time, NaiveTime::from_hms_opt(8, 0, 0));
+}
+
+#[test]
+fn rejects_unknown_fields_in_the_file() {
+    let path = config_file("token = \"token\"\nintervall_ms = 5000\n");
+
+    let err = load_error(&vars(&[("LEDIT_CONFIG", path.to_str().unwrap())]));
+    fs::remove_file(&path).unwrap();
+
+    assert!(err.contains("failed to parse"));
+    assert!(err.contains("intervall_ms"));
+}
+
+#[test]
+fn rejects_invalid_values() {
+    assert_eq!(
+        load_error(&vars(&[("DEFAULT_TIMEZONE", "Europe/Nowhere")])),
+        "Config Error: invalid value for `DEFAULT_TIMEZONE`: unknown timezone"
+    );
+    assert_eq!(
+        load_error(&vars(&[("REMINDER_TIME", "9 am")])),
+        "Config Error: invalid value for `REMINDER_TIME`: expected a time like 09:30"
+    );
+    assert_eq!(
+        load_error(&vars(&[("INTERVAL_MS", "soon")])),
+        "Config Error: invalid value for `INTERVAL_MS`: failed to parse value"
+    );
+    assert_eq!(
+        load_error(&vars(&[("INTERVAL_MS", "500")])),
+        "Config Error: invalid value for `INTERVAL_MS`: must be at least 1000"
+    );
+}
+
+#[test]
+fn needs_two_pool_connections() {
+    let mut config = Config::from_vars(&vars(&[])).unwrap();
+
+    for (pool_max_connections, valid) in [(1, false), (2, true), (100, true), (101, false)] {
+        config.pool_max_connections = pool_max_connections;
+        assert_eq!(config.validate().is_ok(), valid);
+    }
+}
+
+#[test]
+fn simulates_on_throwaway_databases_only() {
+    let mut config = Config::from_vars(&vars(&[("SIMULATION_STEP_MINUTES", "60")])).unwrap();
+    assert!(config.validate().is_ok());
+
+    config.database_url = "postgres://localhost/ledit".to_string();
+    assert_eq!(
+        config.validate().unwrap_err().to_string(),
+        "Config Error: invalid value for `SIMULATION_STEP_MINUTES`: needs an in-memory database or SIMULATION_THROWAWAY_DATABASE=true"
+    );
+
+    config.simulation_throwaway_database = true;
+    assert!(config.validate().is_ok());
+}
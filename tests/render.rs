@@ -0,0 +1,279 @@
+use chrono::NaiveDate;
+use ledit::{
+    testing::{fill, render, COMMANDS, DE, EN},
+    AssignedTodo, ChatMember, Language, Todo, UserTodo,
+};
+use uuid::Uuid;
+
+const CHAT_ID: i64 = -100;
+
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2022, 2, 8).unwrap()
+}
+
+fn member_id(telegram_user_id: i64) -> Uuid {
+    Uuid::from_u128(telegram_user_id as u128)
+}
+
+fn display_name(telegram_user_id: i64) -> &'static str {
+    match telegram_user_id {
+        1 => "alice",
+        _ => "bob",
+    }
+}
+
+fn todo(
+    n: u128,
+    description: &str,
+    interval_days: Option<i64>,
+    assigned_to: i64,
+    scheduled_days_ago: i64,
+    done_by: Option<i64>,
+) -> Todo {
+    Todo {
+        id: Uuid::from_u128(100 + n),
+        chat_id: CHAT_ID,
+        description: description.to_string(),
+
+        interval_days,
+        assigned_user: member_id(assigned_to),
+        scheduled_for: today() - chrono::Duration::days(scheduled_days_ago),
+        done_by: done_by.map(member_id),
+
+        created_by: Some(member_id(1)),
+        created_at: None,
+    }
+}
+
+fn chat_member(telegram_user_id: i64) -> ChatMember {
+    ChatMember {
+        id: member_id(telegram_user_id),
+        telegram_user_id,
+        chat_id: CHAT_ID,
+        display_name: display_name(telegram_user_id).to_string(),
+        todo_weight: 100,
+
+        username: None,
+        first_name: display_name(telegram_user_id).to_string(),
+        last_name: None,
+    }
+}
+
+fn assigned(todo: &Todo, telegram_user_id: i64) -> AssignedTodo {
+    AssignedTodo {
+        id: todo.id,
+        chat_id: todo.chat_id,
+        description: todo.description.clone(),
+
+        interval_days: todo.interval_days,
+        assigned_user: todo.assigned_user,
+        scheduled_for: todo.scheduled_for,
+        done_by: todo.done_by,
+
+        telegram_user_id,
+        display_name: display_name(telegram_user_id).to_string(),
+    }
+}
+
+/// All kinds of todos, sorted like the storage does.
+fn todos() -> Vec<Todo> {
+    vec![
+        todo(1, "Buy milk", None, 1, 0, None),
+        todo(2, "Take out trash", None, 2, 2, None),
+        todo(3, "Water plants", Some(1), 1, 0, Some(1)),
+        todo(4, "Vacuum <living room> & hall", Some(3), 1, 0, Some(2)),
+        todo(5, "Clean mirrors", Some(7), 2, 3, Some(2)),
+    ]
+}
+
+/// The todos of `todos()` that are shown for today.
+fn actionable_todos() -> Vec<AssignedTodo> {
+    let todos = todos();
+
+    vec![
+        assigned(&todos[3], 1),
+        assigned(&todos[2], 1),
+        assigned(&todos[0], 1),
+        assigned(&todos[1], 2),
+    ]
+}
+
+#[test]
+fn renders_todo_list() {
+    insta::assert_snapshot!(render::todo_list(&todos(), today(), &EN));
+    insta::assert_snapshot!("todo_list_empty", render::todo_list(&[], today(), &EN));
+}
+
+#[test]
+fn renders_todos_by_member() {
+    insta::assert_snapshot!(render::todos_by_member(&actionable_todos(), today(), &EN));
+    insta::assert_snapshot!(
+        "todos_by_member_empty",
+        render::todos_by_member(&[], today(), &EN)
+    );
+}
+
+#[test]
+fn renders_todo_overview() {
+    insta::assert_snapshot!(render::todo_overview(
+        &todos(),
+        &actionable_todos(),
+        today(),
+        &EN
+    ));
+}
+
+#[test]
+fn renders_todo_overview_in_german() {
+    insta::assert_snapshot!(render::todo_overview(
+        &todos(),
+        &actionable_todos(),
+        today(),
+        &DE
+    ));
+}
+
+#[test]
+fn renders_reminder() {
+    let todos = todos();
+
+    insta::assert_snapshot!(render::reminder(
+        &[assigned(&todos[0], 1), assigned(&todos[1], 2)],
+        &EN
+    ));
+}
+
+#[test]
+fn renders_replies_to_todo_changes() {
+    let todos = todos();
+
+    insta::assert_snapshot!("added_todo", render::added_todo(&todos[3], &EN));
+    insta::assert_snapshot!("checked_todo", render::checked_todo(&todos[3]));
+    insta::assert_snapshot!("unchecked_todo", render::checked_todo(&todos[1]));
+    insta::assert_snapshot!(
+        "deleted_todo",
+        render::deleted_todo(&todos[1], &todos[2..], today(), &EN)
+    );
+    insta::assert_snapshot!(
+        "deleting_old_todo",
+        render::deleting_old_todo(&todos[0], &EN)
+    );
+}
+
+#[test]
+fn renders_my_todos() {
+    let todos = todos();
+    let in_chat = |todo: &Todo, chat_id: i64, chat_title: Option<&str>| UserTodo {
+        id: todo.id,
+        chat_id,
+        description: todo.description.clone(),
+
+        interval_days: todo.interval_days,
+        scheduled_for: todo.scheduled_for,
+        done_by: todo.done_by,
+
+        chat_title: chat_title.map(str::to_string),
+    };
+    let my_todos = vec![
+        in_chat(&todos[0], -1, Some("Family & friends")),
+        in_chat(&todos[1], -2, Some("Flat")),
+        in_chat(&todos[3], -2, Some("Flat")),
+        in_chat(&todos[2], 1, None),
+    ];
+
+    insta::assert_snapshot!(render::my_todos(&my_todos, today(), &EN));
+    insta::assert_snapshot!("my_todos_empty", render::my_todos(&[], today(), &EN));
+    assert_eq!(
+        render::my_todo_button(&my_todos[0], &EN),
+        "☑️ Buy milk (Family & friends)"
+    );
+}
+
+#[test]
+fn renders_todo_details() {
+    let todos = todos();
+    let members = vec![chat_member(1), chat_member(2)];
+    let created_on = Some(today() - chrono::Duration::days(3));
+
+    insta::assert_snapshot!(render::todo_details(&todos[3], &members, created_on, &EN));
+    // the creator left and the todo is older than the records of creators
+    insta::assert_snapshot!(
+        "todo_details_of_former_members",
+        render::todo_details(&todos[0], &members[1..], None, &EN)
+    );
+    insta::assert_snapshot!(
+        "todo_details_in_german",
+        render::todo_details(&todos[4], &members, created_on, &DE)
+    );
+}
+
+#[test]
+fn renders_help() {
+    insta::assert_snapshot!(render::help(COMMANDS, Language::English));
+    insta::assert_snapshot!(
+        "renders_help_in_german",
+        render::help(COMMANDS, Language::German)
+    );
+}
+
+#[test]
+fn fills_templates_once() {
+    assert_eq!(
+        fill("{a} and {b}", &[("a", "{b}"), ("b", "two")]),
+        "{b} and two"
+    );
+    assert_eq!(fill("{unknown} {", &[("a", "one")]), "{unknown} {");
+}
+
+#[test]
+fn splits_long_messages_at_line_breaks() {
+    assert_eq!(render::split_message("", 10), vec![""]);
+    assert_eq!(render::split_message("short", 10), vec!["short"]);
+    assert_eq!(
+        render::split_message("first\nsecond\nthird", 13),
+        vec!["first\nsecond", "third"]
+    );
+    // blank lines between parts are dropped
+    assert_eq!(
+        render::split_message("first\n\nsecond", 6),
+        vec!["first", "second"]
+    );
+    // lines longer than the limit are cut, counting characters not bytes
+    assert_eq!(
+        render::split_message("✅✅✅✅✅\nok", 2),
+        vec!["✅✅", "✅✅", "✅", "ok"]
+    );
+    // emojis outside the basic plane count twice, like telegram counts them
+    assert_eq!(render::split_message("👍👍\n👍", 5), vec!["👍👍", "👍"]);
+    assert_eq!(render::split_message("🧹🧹🧹", 3), vec!["🧹", "🧹", "🧹"]);
+    let text = "🧽 Clean the bathroom 🛁🚿\n".repeat(500);
+    let parts = render::split_message(&text, 4096);
+    assert!(parts.len() > 3);
+    assert!(parts.iter().all(|v| v.encode_utf16().count() <= 4096));
+}
+
+#[test]
+fn splits_long_todo_lists_without_breaking_formatting() {
+    let todos = (0..200)
+        .map(|n| {
+            todo(
+                n,
+                &format!("Todo number {} with a long description", n),
+                None,
+                1,
+                1,
+                None,
+            )
+        })
+        .collect::<Vec<Todo>>();
+
+    let text = render::todo_list(&todos, today(), &EN);
+    let parts = render::split_message(&text, 4096);
+
+    assert!(parts.len() > 1);
+    assert!(parts.iter().all(|v| v.encode_utf16().count() <= 4096));
+    assert!(parts
+        .iter()
+        .all(|v| v.matches("<b>").count() == v.matches("</b>").count()));
+    assert_eq!(parts.join("\n"), text);
+}